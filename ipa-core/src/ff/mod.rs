@@ -68,6 +68,9 @@ pub trait Serializable: Sized {
     /// Serialize this message to a mutable slice. It is enforced at compile time or on the caller
     /// side that this slice is sized to fit this instance. Implementations do not need to check
     /// the buffer size.
+    ///
+    /// The output is the wire format shared by all helpers. Fields and integers are written in
+    /// little-endian byte order.
     fn serialize(&self, buf: &mut GenericArray<u8, Self::Size>);
 
     /// Deserialize message from a sequence of bytes. Similar to [`serialize`], it is enforced that
@@ -402,6 +402,14 @@ macro_rules! field_impl {
                 assert_eq!(ground_truth, elements);
             }
 
+            #[test]
+            #[should_panic(expected = "assertion `left != right` failed")]
+            fn batch_invert_rejects_zero() {
+                let mut elements = [$field::ONE; 10];
+                elements[4] = $field::ZERO;
+                batch_invert(&mut elements);
+            }
+
             proptest! {
 
                 #[test]
@@ -414,6 +422,15 @@ macro_rules! field_impl {
                     assert_eq!(field_v, $field::deserialize_unchecked(&buf));
                 }
 
+                #[test]
+                #[allow(clippy::ignored_unit_patterns)]
+                fn serialize_little_endian(v in 0..$field::PRIME) {
+                    let mut buf = GenericArray::default();
+                    $field(v).serialize(&mut buf);
+
+                    assert_eq!(v.to_le_bytes().as_slice(), buf.as_slice());
+                }
+
                 #[test]
                 #[allow(clippy::ignored_unit_patterns)]
                 fn deserialize_fail_if_greater_than_prime(v in $field::PRIME..) {
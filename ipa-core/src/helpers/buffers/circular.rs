@@ -122,6 +122,25 @@ impl CircularBuf {
         self.closed = true;
     }
 
+    /// Changes the size of reads from this buffer. It only affects reads that happen after this
+    /// call, data that has been taken already is not affected.
+    ///
+    /// ## Panics
+    /// If `read_size` is 0, larger than capacity, or not a multiple of `write_size`.
+    pub fn set_read_size(&mut self, read_size: usize) {
+        debug_assert!(
+            read_size > 0 && read_size <= self.capacity(),
+            "Read size \"{read_size}\" must be greater than zero and not exceed capacity \"{}\"",
+            self.capacity()
+        );
+        debug_assert!(
+            read_size % self.write_size == 0,
+            "\"{}\" write size must divide read_size \"{read_size}\"",
+            self.write_size
+        );
+        self.read_size = read_size;
+    }
+
     /// Returns a handle that allows to perform a single write to the buffer. Write must be exactly
     /// `write_size` bytes long and buffer must be open for writes and have sufficient capacity
     /// to fit it. [`can_write`] can be used to check all of these conditions.
@@ -192,6 +211,12 @@ impl CircularBuf {
         }
     }
 
+    /// Returns the size of reads from this buffer. Only the last reads of a closed buffer
+    /// can be shorter than that.
+    pub fn read_size(&self) -> usize {
+        self.read_size
+    }
+
     /// Returns `true` if this buffer can be read from.
     pub fn can_read(&self) -> bool {
         (self.closed && !self.is_empty()) || self.len() >= self.read_size
@@ -212,7 +237,8 @@ impl CircularBuf {
         self.data.len()
     }
 
-    fn is_empty(&self) -> bool {
+    /// Returns `true` if this buffer holds no bytes.
+    pub fn is_empty(&self) -> bool {
         self.read == self.write
     }
 
@@ -3,7 +3,7 @@ mod unordered_receiver;
 
 mod circular;
 
-pub use ordering_sender::OrderingSender;
+pub use ordering_sender::{Error as OrderingSenderError, OrderingSender};
 pub use unordered_receiver::{
     DeserializeError, EndOfStreamError, Error as UnorderedReceiverError, UnorderedReceiver,
 };
@@ -3,7 +3,9 @@ use std::{
     cmp::Ordering,
     collections::VecDeque,
     fmt::Debug,
+    future::poll_fn,
     marker::PhantomData,
+    mem::take,
     num::NonZeroUsize,
     pin::Pin,
     task::{Context, Poll},
@@ -15,29 +17,50 @@ use crate::{
     helpers::{buffers::circular::CircularBuf, Message},
     sync::{
         atomic::{
-            AtomicUsize,
-            Ordering::{AcqRel, Acquire},
+            AtomicBool, AtomicUsize,
+            Ordering::{AcqRel, Acquire, Release},
         },
         Mutex, MutexGuard,
     },
 };
 
+/// An error raised by [`OrderingSender`] when a message can not be sent.
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum Error {
+    #[error("the receiving end of this sender is closed")]
+    Closed,
+    #[error("this sender was aborted")]
+    Aborted,
+}
+
 /// The operating state for an `OrderingSender`.
 struct State {
     /// A store of bytes to write into.
     buf: CircularBuf,
+    /// Set when nothing is going to read from this sender anymore.
+    receiver_closed: bool,
     /// An entity to wake when the buffer is read from.
     write_ready: Option<Waker>,
     /// Another entity to wake when the buffer is read from.
     stream_ready: Option<Waker>,
+    /// The largest number of bytes ever held in `buf`.
+    high_water_mark: usize,
+    /// Entities to wake once the buffer is closed and everything in it has been read.
+    drained: Vec<Waker>,
+    /// The number of messages written into `buf`.
+    written: usize,
 }
 
 impl State {
     fn new(capacity: usize, write_size: usize, read_threshold: usize) -> Self {
         Self {
             buf: CircularBuf::new(capacity, write_size, read_threshold),
+            receiver_closed: false,
             write_ready: None,
             stream_ready: None,
+            high_water_mark: 0,
+            drained: Vec::new(),
+            written: 0,
         }
     }
 
@@ -72,6 +95,8 @@ impl State {
         }
 
         self.buf.next().write(m);
+        self.written += 1;
+        self.high_water_mark = self.high_water_mark.max(self.buf.len());
 
         if self.buf.can_read() {
             Self::wake(&mut self.stream_ready);
@@ -90,6 +115,9 @@ impl State {
                 // the buffer
                 Self::wake(&mut self.write_ready);
             }
+            if self.is_drained() {
+                self.wake_drained();
+            }
 
             Poll::Ready(next)
         } else {
@@ -101,11 +129,26 @@ impl State {
     fn close(&mut self) {
         self.buf.close();
         Self::wake(&mut self.stream_ready);
+        if self.is_drained() {
+            self.wake_drained();
+        }
     }
 
     fn is_closed(&self) -> bool {
         self.buf.is_closed()
     }
+
+    /// Nothing is ever going to be read from this buffer again, either because it is closed and
+    /// empty, or because the reading end went away.
+    fn is_drained(&self) -> bool {
+        (self.buf.is_closed() && self.buf.is_empty()) || self.receiver_closed
+    }
+
+    fn wake_drained(&mut self) {
+        for w in self.drained.drain(..) {
+            w.wake();
+        }
+    }
 }
 
 /// An saved waker for a given index.
@@ -224,6 +267,16 @@ impl Waiting {
         self.shard(i).wake(i);
     }
 
+    /// Wakes every saved waker, no matter which index it is waiting for.
+    fn wake_all(&self) {
+        for shard in &self.shards {
+            let wakers = take(&mut shard.lock().unwrap().wakers);
+            for item in wakers {
+                item.w.wake();
+            }
+        }
+    }
+
     /// Returns all records currently waiting to be sent in sorted order.
     #[cfg(feature = "stall-detection")]
     fn waiting(&self) -> std::collections::BTreeSet<usize> {
@@ -273,6 +326,8 @@ pub struct OrderingSender {
     next: AtomicUsize,
     state: Mutex<State>,
     waiting: Waiting,
+    /// Set by [`OrderingSender::abort`].
+    aborted: AtomicBool,
 }
 
 impl OrderingSender {
@@ -293,6 +348,7 @@ impl OrderingSender {
                 read_threshold.get(),
             )),
             waiting: Waiting::default(),
+            aborted: AtomicBool::new(false),
         }
     }
 
@@ -300,6 +356,11 @@ impl OrderingSender {
     /// This method blocks until all previous messages are sent and until sufficient
     /// space becomes available in the sender's buffer.
     ///
+    /// # Errors
+    ///
+    /// The future this method returns resolves to an error if the receiving end of this
+    /// sender is closed (see [`close_receiver`]) or if this sender is aborted (see [`abort`]).
+    ///
     /// # Panics
     ///
     /// Polling the future this method returns will panic if
@@ -307,6 +368,8 @@ impl OrderingSender {
     /// * the same index is provided more than once.
     ///
     /// [capacity]: OrderingSender#spare-capacity-configuration
+    /// [`close_receiver`]: OrderingSender::close_receiver
+    /// [`abort`]: OrderingSender::abort
     pub fn send<M: Message, B: Borrow<M>>(&self, i: usize, m: B) -> Send<'_, M, B> {
         Send {
             i,
@@ -319,6 +382,8 @@ impl OrderingSender {
     /// Close the sender at index `i`.
     /// This method blocks until all previous messages are sent.
     ///
+    /// If this sender is aborted, the future this method returns resolves right away.
+    ///
     /// # Panics
     /// Polling the future this method returns will panic if a message has already
     /// been sent with an equal or higher index.
@@ -326,6 +391,103 @@ impl OrderingSender {
         Close { i, sender: self }
     }
 
+    /// Signals that nothing is going to read from this sender anymore. All pending and
+    /// future writes to it fail with [`Error::Closed`] instead of waiting for buffer space
+    /// that will never be available.
+    ///
+    /// ## Panics
+    /// If the underlying mutex is poisoned or locked by the same thread.
+    pub fn close_receiver(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.receiver_closed = true;
+        State::wake(&mut state.write_ready);
+        state.wake_drained();
+        drop(state);
+
+        // writer at `next` may not have acquired the state yet
+        self.waiting.wake(self.next.load(Acquire));
+    }
+
+    /// Closes this sender right away, no matter which index it is at. Data written before
+    /// this call can still be taken from it. All pending and future writes fail with
+    /// [`Error::Aborted`] and closes complete without doing anything, so nobody is left
+    /// waiting for a turn that will never come. Returns the number of messages written into
+    /// this sender.
+    ///
+    /// ## Panics
+    /// If the underlying mutex is poisoned or locked by the same thread.
+    pub fn abort(&self) -> usize {
+        // Writers check this flag before taking their turn and again before giving up on
+        // it, so setting it before waking them means none of them can miss it.
+        self.aborted.store(true, Release);
+        let mut state = self.state.lock().unwrap();
+        if !state.is_closed() {
+            state.close();
+        }
+        State::wake(&mut state.write_ready);
+        let written = state.written;
+        drop(state);
+
+        self.waiting.wake_all();
+        written
+    }
+
+    fn is_aborted(&self) -> bool {
+        self.aborted.load(Acquire)
+    }
+
+    /// Resolves once this sender is closed and everything written into it has been taken by
+    /// the reading end. It also resolves if the reading end is closed, as nothing is going to
+    /// be taken after that.
+    ///
+    /// ## Panics
+    /// If the underlying mutex is poisoned or locked by the same thread.
+    pub async fn drained(&self) {
+        poll_fn(|cx| {
+            let mut state = self.state.lock().unwrap();
+            if state.is_drained() {
+                Poll::Ready(())
+            } else {
+                if !state.drained.iter().any(|w| w.will_wake(cx.waker())) {
+                    state.drained.push(cx.waker().clone());
+                }
+                Poll::Pending
+            }
+        })
+        .await;
+    }
+
+    /// Changes the number of bytes that must be buffered before they become available for reading.
+    ///
+    /// ## Panics
+    /// If the underlying mutex is poisoned or locked by the same thread, or if `read_threshold`
+    /// exceeds the capacity or is not a multiple of the write size.
+    pub fn set_read_threshold(&self, read_threshold: NonZeroUsize) {
+        let mut state = self.state.lock().unwrap();
+        state.buf.set_read_size(read_threshold.get());
+        if state.buf.can_read() {
+            State::wake(&mut state.stream_ready);
+        }
+    }
+
+    /// Returns the number of bytes written into this sender that the receiving
+    /// end has not taken yet.
+    ///
+    /// ## Panics
+    /// If the underlying mutex is poisoned or locked by the same thread.
+    pub fn buffered(&self) -> usize {
+        self.state.lock().unwrap().buf.len()
+    }
+
+    /// Returns the largest number of bytes this sender has buffered at any point in time.
+    /// Comparing it with the capacity shows whether the buffer is sized right for the channel.
+    ///
+    /// ## Panics
+    /// If the underlying mutex is poisoned or locked by the same thread.
+    pub fn high_water_mark(&self) -> usize {
+        self.state.lock().unwrap().high_water_mark
+    }
+
     /// Returns `true` if this sender is closed for writes.
     ///
     /// ## Panics
@@ -334,10 +496,15 @@ impl OrderingSender {
         self.state.lock().unwrap().is_closed()
     }
 
+    /// Returns the index of the next message to be sent.
+    pub fn next_index(&self) -> usize {
+        self.next.load(Acquire)
+    }
+
     /// Perform the next `send` or `close` operation.
-    fn next_op<F>(&self, i: usize, cx: &Context<'_>, f: F) -> Poll<()>
+    fn next_op<F, T>(&self, i: usize, cx: &Context<'_>, f: F) -> Poll<T>
     where
-        F: FnOnce(&mut MutexGuard<'_, State>) -> Poll<()>,
+        F: FnOnce(&mut MutexGuard<'_, State>) -> Poll<T>,
     {
         // This load here is on the hot path.
         // Don't acquire the state mutex unless this test passes.
@@ -388,6 +555,17 @@ impl OrderingSender {
     /// ## Panics
     /// If the internal mutex is poisoned or locked by this thread already.
     pub fn take_next(&self, cx: &Context<'_>) -> Poll<Option<Vec<u8>>> {
+        self.take_next_sized(cx)
+            .map(|chunk| chunk.map(|(data, _)| data))
+    }
+
+    /// Same as [`Self::take_next`], but also returns the read threshold that was in effect
+    /// when the chunk was taken. Chunks shorter than that were only made available because
+    /// this sender is closed.
+    ///
+    /// ## Panics
+    /// If the internal mutex is poisoned or locked by this thread already.
+    pub fn take_next_sized(&self, cx: &Context<'_>) -> Poll<Option<(Vec<u8>, usize)>> {
         let mut b = self.state.lock().unwrap();
 
         if let Poll::Ready(v) = b.take(cx) {
@@ -399,7 +577,7 @@ impl OrderingSender {
                 "take_next ready"
             );
             self.waiting.wake(next);
-            Poll::Ready(Some(v))
+            Poll::Ready(Some((v, b.buf.read_size())))
         } else if b.is_closed() {
             Poll::Ready(None)
         } else {
@@ -450,19 +628,32 @@ pub struct Send<'a, M: Message, B: Borrow<M> + 'a> {
 }
 
 impl<'a, M: Message, B: Borrow<M> + 'a> Future for Send<'a, M, B> {
-    type Output = ();
+    type Output = Result<(), Error>;
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         let this = self.as_mut();
+        if this.sender.is_aborted() {
+            return Poll::Ready(Err(Error::Aborted));
+        }
 
         let res = this.sender.next_op(this.i, cx, |b| {
+            if this.sender.is_aborted() {
+                return Poll::Ready(Err(Error::Aborted));
+            }
             assert!(!b.is_closed(), "writing on a closed stream");
-            b.write(this.m.borrow(), cx)
+            if b.receiver_closed {
+                // Still counts as done, so the next writer in line can observe it as well.
+                return Poll::Ready(Err(Error::Closed));
+            }
+            b.write(this.m.borrow(), cx).map(Ok)
         });
         // A successful write: wake the next in line.
         // But not while holding the lock on state.
         if res.is_ready() {
             this.sender.waiting.wake(this.i + 1);
+        } else if this.sender.is_aborted() {
+            // Aborted after the check above, possibly before the waker was saved.
+            return Poll::Ready(Err(Error::Aborted));
         }
         res
     }
@@ -479,10 +670,20 @@ impl Future for Close<'_> {
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         let this = self.as_mut();
-        this.sender.next_op(this.i, cx, |b| {
-            b.close();
+        if this.sender.is_aborted() {
+            return Poll::Ready(());
+        }
+        let res = this.sender.next_op(this.i, cx, |b| {
+            // `abort` closes the buffer itself.
+            if !this.sender.is_aborted() {
+                b.close();
+            }
             Poll::Ready(())
-        })
+        });
+        if res.is_pending() && this.sender.is_aborted() {
+            return Poll::Ready(());
+        }
+        res
     }
 }
 
@@ -527,7 +728,7 @@ mod test {
     use shuttle::future as tokio;
     use typenum::Unsigned;
 
-    use super::OrderingSender;
+    use super::{Error, OrderingSender};
     use crate::{
         ff::{Fp31, Fp32BitPrime, Gf9Bit, PrimeField, Serializable, U128Conversions},
         helpers::MpcMessage,
@@ -551,7 +752,7 @@ mod test {
         run(|| async {
             let input = Fp31::truncate_from(7_u128);
             let sender = sender::<Fp31>();
-            sender.send(0, input).await;
+            sender.send(0, input).await.unwrap();
             assert!(sender.as_stream().next().now_or_never().is_none());
         });
     }
@@ -597,7 +798,7 @@ mod test {
             let send_many =
                 join_all((0..3_u8).map(|i| sender.send(usize::from(i), Fp31::truncate_from(i))));
             let send_again = sender.send(2, Fp31::truncate_from(2_u128));
-            join(send_many, send_again).await;
+            let _ = join(send_many, send_again).await;
         });
     }
 
@@ -613,6 +814,67 @@ mod test {
         });
     }
 
+    /// Closing the receiver unblocks writers waiting for buffer space and writers waiting
+    /// for their turn.
+    #[test]
+    fn close_receiver() {
+        run(|| async {
+            let sender = sender::<Fp31>();
+            for i in 0..6 {
+                sender.send(i, Fp31::ZERO).await.unwrap();
+            }
+
+            // buffer is now full
+            let mut blocked = pin!(sender.send(6, Fp31::ZERO));
+            let mut waiting = pin!(sender.send(7, Fp31::ZERO));
+            assert_eq!(None, poll_immediate(&mut blocked).await);
+            assert_eq!(None, poll_immediate(&mut waiting).await);
+
+            sender.close_receiver();
+            assert_eq!(
+                (Err(Error::Closed), Err(Error::Closed)),
+                join(blocked, waiting).await
+            );
+            assert_eq!(Err(Error::Closed), sender.send(8, Fp31::ZERO).await);
+        });
+    }
+
+    /// Aborting unblocks writers and closes that are waiting for a turn that never comes,
+    /// and data written before that can still be read.
+    #[test]
+    fn abort() {
+        run(|| async {
+            let sender = sender::<Fp31>();
+            for i in 0..2 {
+                sender
+                    .send(i, Fp31::truncate_from(i as u128))
+                    .await
+                    .unwrap();
+            }
+
+            // record 2 is never sent
+            let mut waiting = pin!(sender.send(3, Fp31::ZERO));
+            let mut close = pin!(sender.close(4));
+            assert_eq!(None, poll_immediate(&mut waiting).await);
+            assert_eq!(None, poll_immediate(&mut close).await);
+
+            assert_eq!(2, sender.abort());
+            assert_eq!(Err(Error::Aborted), waiting.await);
+            close.await;
+            assert_eq!(Err(Error::Aborted), sender.send(2, Fp31::ZERO).await);
+            assert!(sender.is_closed());
+
+            let taken = sender.as_stream().collect::<Vec<_>>().await;
+            let flat = taken.into_iter().flatten().collect::<Vec<_>>();
+            assert_eq!(
+                vec![Fp31::truncate_from(0_u128), Fp31::truncate_from(1_u128)],
+                flat.chunks(<Fp31 as Serializable>::Size::USIZE)
+                    .map(|b| Fp31::deserialize_unchecked(GenericArray::from_slice(b)))
+                    .collect::<Vec<_>>()
+            );
+        });
+    }
+
     #[test]
     #[should_panic(expected = "writing on a closed stream")]
     fn send_after_close() {
@@ -621,7 +883,7 @@ mod test {
             // We can't use `join()` here because the close task won't bother to
             // wake the send task if the send is polled first.
             sender.close(0).await;
-            sender.send(1, Fp31::truncate_from(1_u128)).await;
+            sender.send(1, Fp31::truncate_from(1_u128)).await.unwrap();
         });
     }
 
@@ -635,7 +897,7 @@ mod test {
     // Given a message, returns a closure that sends the message and increments an associated record index.
     fn send_fn<M: MpcMessage>(m: M) -> BoxedSendFn {
         Box::new(|s: &OrderingSender, i: &mut usize| {
-            let fut = s.send(*i, m).boxed();
+            let fut = s.send(*i, m).map(Result::unwrap).boxed();
             *i += 1;
             fut
         })
@@ -657,7 +919,8 @@ mod test {
             for i in 0..COUNT {
                 sender
                     .send(i, Fp32BitPrime::truncate_from(u128::try_from(i).unwrap()))
-                    .await;
+                    .await
+                    .unwrap();
             }
 
             // buffer is now full.
@@ -670,16 +933,40 @@ mod test {
             drop(poll_fn(|ctx| sender.take_next(ctx)).await);
 
             // now we can send again.
-            assert_eq!(Some(()), poll_immediate(f).await);
+            assert_eq!(Some(Ok(())), poll_immediate(f).await);
 
             for i in (COUNT + 1)..(2 * COUNT) {
                 sender
                     .send(i, Fp32BitPrime::truncate_from(u128::try_from(i).unwrap()))
-                    .await;
+                    .await
+                    .unwrap();
             }
         });
     }
 
+    /// The high-water mark keeps the peak fill level after the buffer is drained.
+    #[test]
+    fn high_water_mark() {
+        const SZ: usize = <<Fp31 as Serializable>::Size as Unsigned>::USIZE;
+        run(|| async {
+            let sender = sender::<Fp31>();
+            let burst = 6;
+            for i in 0..burst {
+                sender
+                    .send(i, Fp31::truncate_from(u128::try_from(i).unwrap()))
+                    .await
+                    .unwrap();
+            }
+            assert_eq!(burst * SZ, sender.high_water_mark());
+
+            drop(poll_fn(|ctx| sender.take_next(ctx)).await);
+            sender.send(burst, Fp31::ZERO).await.unwrap();
+
+            assert_eq!(SZ, sender.buffered());
+            assert_eq!(burst * SZ, sender.high_water_mark());
+        });
+    }
+
     #[test]
     #[should_panic(expected = "Expect to keep messages of size 4, got 2")]
     fn invalid_uneven_size() {
@@ -719,7 +1006,8 @@ mod test {
             );
             sender
                 .send(0, crate::ff::Gf20Bit::truncate_from(0_u128))
-                .await;
+                .await
+                .unwrap();
         });
     }
 
@@ -774,7 +1062,10 @@ mod test {
                 tokio::spawn({
                     let sender = Arc::clone(&sender);
                     async move {
-                        sender.send(i, Fp31::truncate_from(i as u128)).await;
+                        sender
+                            .send(i, Fp31::truncate_from(i as u128))
+                            .await
+                            .unwrap();
                     }
                 })
             }))
@@ -822,7 +1113,7 @@ mod test {
                     read_barrier.wait().await;
                     write_barrier.wait().await;
                     // f should be resolved if `take` is implemented correctly.
-                    assert_eq!(poll_immediate(f).await, Some(()));
+                    assert_eq!(poll_immediate(f).await, Some(Ok(())));
                 }
             });
 
@@ -2,11 +2,15 @@ use std::{
     marker::PhantomData,
     mem::take,
     num::NonZeroUsize,
+    ops::Range,
     pin::Pin,
     task::{Context, Poll},
 };
 
-use futures::{task::Waker, Future, Stream};
+use futures::{
+    task::{noop_waker_ref, Waker},
+    Future, Stream,
+};
 use generic_array::GenericArray;
 use typenum::Unsigned;
 
@@ -56,6 +60,7 @@ where
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         let this = self.as_ref();
         let mut recv = this.shared_state.lock().unwrap();
+        recv.note_requested(this.i);
         if recv.is_next(this.i) {
             recv.poll_next(cx)
         } else {
@@ -65,6 +70,61 @@ where
     }
 }
 
+/// A future for receiving a contiguous range of items from an `UnorderedReceiver`.
+/// It resolves once every item in the range has been received.
+pub struct RangeReceiver<S, C, M>
+where
+    S: Stream<Item = C> + Send,
+    C: AsRef<[u8]>,
+    M: Message,
+{
+    range: Range<usize>,
+    received: Vec<M>,
+    shared_state: Arc<Mutex<OperatingState<S, C>>>,
+}
+
+// Received items are never pinned, so it is fine to move them around.
+impl<S, C, M> Unpin for RangeReceiver<S, C, M>
+where
+    S: Stream<Item = C> + Send,
+    C: AsRef<[u8]>,
+    M: Message,
+{
+}
+
+impl<S, C, M> Future for RangeReceiver<S, C, M>
+where
+    S: Stream<Item = C> + Send,
+    C: AsRef<[u8]>,
+    M: Message,
+{
+    type Output = Result<Vec<M>, Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let mut recv = this.shared_state.lock().unwrap();
+        if let Some(last) = this.range.clone().last() {
+            recv.note_requested(last);
+        }
+        loop {
+            let i = this.range.start + this.received.len();
+            if i == this.range.end {
+                return Poll::Ready(Ok(take(&mut this.received)));
+            }
+            if recv.is_next(i) {
+                match recv.poll_next(cx) {
+                    Poll::Ready(Ok(m)) => this.received.push(m),
+                    Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                    Poll::Pending => return Poll::Pending,
+                }
+            } else {
+                recv.add_waker(i, cx.waker());
+                return Poll::Pending;
+            }
+        }
+    }
+}
+
 /// Saved unread data from a received chunk.
 #[derive(Default)]
 struct Spare {
@@ -135,6 +195,9 @@ where
     next: usize,
     /// The maximum value that has ever been requested to receive.
     max_polled_idx: Option<usize>,
+    /// The largest index any receive has been polled for, including ranges that are only
+    /// partially received.
+    max_requested: Option<usize>,
     /// The underlying stream can provide chunks of data larger than a single
     /// message.  Save any spare data here.
     spare: Spare,
@@ -182,6 +245,10 @@ where
         i == self.next
     }
 
+    fn note_requested(&mut self, i: usize) {
+        self.max_requested = std::cmp::max(self.max_requested, Some(i));
+    }
+
     /// Track a waker from a future that was invoked before data was ready.
     ///
     /// # Panics
@@ -263,6 +330,43 @@ where
         }
     }
 
+    /// Attempt to read record `i` without waiting for it to arrive. Only the next record can
+    /// be read this way, as records are read off the stream in order. Nothing is registered
+    /// when the record is not available, so callers are expected to try again later.
+    ///
+    /// If a [`recv`] for `i` has been polled and is still pending, this returns `None` without
+    /// touching the stream.
+    ///
+    /// [`recv`]: UnorderedReceiver::recv
+    ///
+    /// # Panics
+    /// If `i` is for a message that has already been read.
+    fn try_next<M: Message>(&mut self, i: usize) -> Option<Result<M, Error>> {
+        assert!(
+            i >= self.next,
+            "Attempting to read a record (record = {i}) that has already been fulfilled. Read cursor is currently at {}", self.next
+        );
+        if !self.is_next(i) {
+            return None;
+        }
+        // A pending receive for `self.next` owns the waker registered on the stream. Polling
+        // the stream here would replace it with a no-op waker, and that receive would never be
+        // woken up. A receive that was dropped while pending looks the same, which is harmless:
+        // the next `recv` picks it up.
+        if self.max_polled_idx == Some(self.next) {
+            return None;
+        }
+
+        let max_polled_idx = self.max_polled_idx;
+        match self.poll_next(&mut Context::from_waker(noop_waker_ref())) {
+            Poll::Ready(r) => Some(r),
+            Poll::Pending => {
+                self.max_polled_idx = max_polled_idx;
+                None
+            }
+        }
+    }
+
     #[cfg(feature = "stall-detection")]
     fn waiting(&self) -> impl Iterator<Item = usize> + '_ {
         let start = self.next % self.wakers.len();
@@ -317,6 +421,7 @@ where
                 stream,
                 next: 0,
                 max_polled_idx: None,
+                max_requested: None,
                 spare: Spare::default(),
                 wakers,
                 overflow_wakers: Vec::new(),
@@ -340,6 +445,56 @@ where
         }
     }
 
+    /// Receive all items in the given range from the stream. Unlike calling [`recv`] for
+    /// every index, this creates a single future that tracks only one waker at a time.
+    ///
+    /// ## Cancellation
+    /// The returned future is not cancel-safe. Items are read off the stream as soon as
+    /// they arrive and are kept inside the future until the whole range is received. If the
+    /// future is dropped before that, the items read so far are lost, and calling [`recv`]
+    /// for any of them afterwards panics. Items that the future did not reach yet can still
+    /// be received.
+    ///
+    /// # Panics
+    ///
+    /// If any index in `range` is requested more than once, same as [`recv`].
+    ///
+    /// [`recv`]: Self::recv
+    pub fn recv_many<M: Message>(&self, range: Range<usize>) -> RangeReceiver<S, C, M> {
+        RangeReceiver {
+            received: Vec::with_capacity(range.len()),
+            range,
+            shared_state: Arc::clone(&self.inner),
+        }
+    }
+
+    /// Receive from the stream at index `i`, if it is available right now. This returns
+    /// `None` if `i` is not the next record to be read, if the data for it hasn't arrived
+    /// yet or if there is a pending [`recv`] for it.
+    ///
+    /// # Panics
+    /// If record `i` has already been received.
+    ///
+    /// [`recv`]: Self::recv
+    pub fn try_recv<M: Message, I: Into<usize>>(&self, i: I) -> Option<Result<M, Error>> {
+        self.inner.lock().unwrap().try_next(i.into())
+    }
+
+    /// Returns the number of records received so far. Records are handed out in order, so
+    /// this is also the index of the next record to be received.
+    pub fn received(&self) -> usize {
+        self.inner.lock().unwrap().next
+    }
+
+    /// Returns the records that receives were issued for, but that have not arrived yet. The
+    /// range starts at the next record to be received and ends after the largest record any
+    /// receive was polled for. Receives that were dropped before completing still count.
+    pub fn expected(&self) -> Range<usize> {
+        let state = self.inner.lock().unwrap();
+        let end = state.max_requested.map_or(0, |i| i + 1);
+        state.next..std::cmp::max(state.next, end)
+    }
+
     #[cfg(feature = "stall-detection")]
     pub fn waiting(&self) -> Vec<usize> {
         let state = self.inner.lock().unwrap();
@@ -372,11 +527,22 @@ impl DeserializeError {
 #[cfg(all(test, any(unit_test, feature = "shuttle")))]
 mod test {
     use std::num::NonZeroUsize;
+    #[cfg(not(feature = "shuttle"))]
+    use std::{
+        pin::pin,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+        task::{Context, Poll},
+    };
 
+    #[cfg(not(feature = "shuttle"))]
+    use futures::task::{waker, ArcWake};
     use futures::{
         future::{try_join, try_join_all},
         stream::iter,
-        Future, Stream,
+        Future, FutureExt, Stream,
     };
     use generic_array::GenericArray;
     use rand::Rng;
@@ -545,8 +711,6 @@ mod test {
     // inject its runtime and this test panics. There is no reason to use Shuttle here.
     #[cfg(not(feature = "shuttle"))]
     fn synchronous() {
-        use futures::FutureExt;
-
         const DATA: &[u8] = &[18, 12];
         let recv = receiver(&[DATA]);
         assert!(recv.recv::<Fp31, _>(1_usize).now_or_never().is_none());
@@ -557,6 +721,152 @@ mod test {
         }
     }
 
+    #[test]
+    #[cfg(not(feature = "shuttle"))]
+    fn try_recv() {
+        const DATA: &[u8] = &[18, 12];
+        let recv = receiver(&[DATA]);
+        // record 1 cannot be read before record 0
+        assert!(recv.try_recv::<Fp31, _>(1_usize).is_none());
+        for (i, &v) in DATA.iter().enumerate() {
+            let f: Fp31 = recv.try_recv(i).unwrap().unwrap();
+            assert_eq!(f, Fp31::try_from(u128::from(v)).unwrap());
+        }
+        assert!(matches!(
+            recv.try_recv::<Fp31, _>(DATA.len()),
+            Some(Err(super::Error::EndOfStream(_)))
+        ));
+    }
+
+    #[test]
+    #[cfg(not(feature = "shuttle"))]
+    fn try_recv_not_received() {
+        let recv = UnorderedReceiver::new(
+            Box::pin(futures::stream::pending::<Vec<u8>>()),
+            NonZeroUsize::new(3).unwrap(),
+        );
+        assert!(recv.try_recv::<Fp31, _>(0_usize).is_none());
+        assert!(recv.try_recv::<Fp31, _>(0_usize).is_none());
+    }
+
+    #[test]
+    #[cfg(not(feature = "shuttle"))]
+    fn try_recv_deserialize_error() {
+        const DATA: &[u8] = &[31];
+        let recv = receiver(&[DATA]);
+        assert!(matches!(
+            recv.try_recv::<Fp31, _>(0_usize),
+            Some(Err(super::Error::DeserializeFailed(_)))
+        ));
+    }
+
+    /// `try_recv` must not steal the stream waker from a receive that is waiting for the
+    /// same record.
+    #[test]
+    #[cfg(not(feature = "shuttle"))]
+    fn try_recv_with_pending_recv() {
+        #[derive(Default)]
+        struct Flag(AtomicBool);
+
+        impl ArcWake for Flag {
+            fn wake_by_ref(arc_self: &Arc<Self>) {
+                arc_self.0.store(true, Ordering::Release);
+            }
+        }
+
+        let (tx, rx) = futures::channel::mpsc::unbounded::<Vec<u8>>();
+        let recv = UnorderedReceiver::new(Box::pin(rx), NonZeroUsize::new(3).unwrap());
+        let woken = Arc::new(Flag::default());
+        let waker = waker(Arc::clone(&woken));
+        let mut cx = Context::from_waker(&waker);
+
+        let mut pending = pin!(recv.recv::<Fp31, _>(0_usize));
+        assert!(pending.as_mut().poll(&mut cx).is_pending());
+        assert!(recv.try_recv::<Fp31, _>(0_usize).is_none());
+
+        tx.unbounded_send(vec![7]).unwrap();
+        assert!(woken.0.load(Ordering::Acquire));
+        assert!(matches!(
+            pending.as_mut().poll(&mut cx),
+            Poll::Ready(Ok(v)) if v == Fp31::truncate_from(7_u128)
+        ));
+    }
+
+    /// Dropping a pending read cancels it. The same records can be read again afterwards.
+    #[test]
+    fn cancelled_recv() {
+        const DATA: &[u8] = &[1, 2, 3, 5, 7, 11];
+
+        run(|| {
+            let recv = receiver(vec![DATA.to_vec()]);
+            async move {
+                // Neither read can complete before record 0 is read. Record 5 does not fit
+                // into the waker slots, so it is registered as an overflow waker.
+                for i in [1_usize, 5] {
+                    assert!(recv.recv::<Fp31, _>(i).now_or_never().is_none());
+                }
+
+                let received = try_join_all((0..DATA.len()).rev().map(|i| recv.recv::<Fp31, _>(i)))
+                    .await
+                    .unwrap();
+                assert_eq!(
+                    DATA.iter()
+                        .rev()
+                        .map(|&v| Fp31::try_from(u128::from(v)).unwrap())
+                        .collect::<Vec<_>>(),
+                    received
+                );
+            }
+        });
+    }
+
+    /// Receive a range of records concurrently with individual reads that precede it.
+    #[test]
+    fn recv_many() {
+        const DATA: &[u8] = &[1, 2, 3, 5, 7, 11, 13, 17, 23, 29];
+        run(|| {
+            let recv = receiver(DATA.chunks(3).map(<[u8]>::to_vec).collect::<Vec<_>>());
+            async move {
+                let (range, _) = try_join(
+                    spawn({
+                        let recv = recv.clone();
+                        async move { recv.recv_many::<Fp31>(2..DATA.len()).await.unwrap() }
+                    }),
+                    try_join_all(DATA[..2].iter().enumerate().rev().map(|(i, &v)| {
+                        spawn({
+                            let recv = recv.clone();
+                            async move {
+                                let f: Fp31 = recv.recv(i).await.unwrap();
+                                assert_eq!(f, Fp31::try_from(u128::from(v)).unwrap());
+                            }
+                        })
+                    })),
+                )
+                .await
+                .unwrap();
+                assert_eq!(
+                    range,
+                    DATA[2..]
+                        .iter()
+                        .map(|&v| Fp31::try_from(u128::from(v)).unwrap())
+                        .collect::<Vec<_>>()
+                );
+            }
+        });
+    }
+
+    #[test]
+    #[cfg(not(feature = "shuttle"))]
+    fn recv_many_empty() {
+        let recv = receiver(Vec::<Vec<u8>>::new());
+        let v = recv
+            .recv_many::<Fp31>(0..0)
+            .now_or_never()
+            .unwrap()
+            .unwrap();
+        assert!(v.is_empty());
+    }
+
     /// Register more reads than the receiver has the capacity to track.
     /// Start by registering those that are furthest into the future to
     /// exercise the overflow tracking mechanism.
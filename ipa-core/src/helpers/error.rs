@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use thiserror::Error;
 
 use crate::{
@@ -27,4 +29,24 @@ pub enum Error<I: TransportIdentity> {
         channel_id: ChannelId<I>,
         total_records: TotalRecords,
     },
+    #[error(
+        "channel {channel_id:?} is closed, either on the receiving side or after a failed send"
+    )]
+    ChannelClosed { channel_id: ChannelId<I> },
+    #[error("timed out after {elapsed:?} sending record {record_id:?} to {channel_id:?}")]
+    SendTimeout {
+        channel_id: ChannelId<I>,
+        record_id: RecordId,
+        /// Time spent sending the record before [`GatewayConfig::send_timeout`] ran out.
+        ///
+        /// [`GatewayConfig::send_timeout`]: crate::helpers::GatewayConfig::send_timeout
+        elapsed: Duration,
+    },
+    #[error("total number of records is not specified for {channel_id:?}, it must be set before sending")]
+    TotalRecordsUnspecified { channel_id: ChannelId<I> },
+    #[error("query deadline exceeded waiting for record {record_id:?} on {channel_id:?}")]
+    DeadlineExceeded {
+        channel_id: ChannelId<I>,
+        record_id: RecordId,
+    },
 }
@@ -7,6 +7,8 @@ mod transport;
 use std::{
     cmp::{max, min},
     num::NonZeroUsize,
+    ops::Range,
+    time::Instant,
 };
 
 pub(super) use receive::{MpcReceivingEnd, ShardReceivingEnd};
@@ -24,10 +26,10 @@ use crate::{
             transport::Transports,
         },
         query::QueryConfig,
-        HelperChannelId, LogErrors, Message, MpcMessage, RecordsStream, Role, RoleAssignment,
-        ShardChannelId, TotalRecords, Transport,
+        HelperChannelId, LogErrors, Message, MpcMessage, PeerStatus, RecordsStream, Role,
+        RoleAssignment, ShardChannelId, TotalRecords, Transport,
     },
-    protocol::QueryId,
+    protocol::{QueryId, RecordId},
     sharding::{ShardConfiguration, ShardIndex},
     sync::{Arc, Mutex},
     utils::NonZeroU32PowerOfTwo,
@@ -57,6 +59,8 @@ pub struct Gateway {
     config: GatewayConfig,
     transports: Transports<RoleResolvingTransport, ShardTransportImpl>,
     query_id: QueryId,
+    /// The moment [`GatewayConfig::query_deadline`] runs out.
+    deadline: Option<Instant>,
     #[cfg(feature = "stall-detection")]
     inner: crate::sync::Arc<State>,
     #[cfg(not(feature = "stall-detection"))]
@@ -71,6 +75,40 @@ pub struct State {
     shard_receivers: GatewayReceivers<ShardIndex, ShardReceiveStream>,
 }
 
+/// Whether a channel reported by [`Gateway::active_channels`] sends or receives records.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChannelKind {
+    Send,
+    Receive,
+}
+
+/// Snapshot of a single MPC channel opened through a [`Gateway`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChannelSummary {
+    pub channel_id: HelperChannelId,
+    pub kind: ChannelKind,
+    /// The number of records sent or received through this channel so far. Sent records
+    /// are counted once they are written in order to the channel buffer, even if they have
+    /// not reached the network yet.
+    pub records: usize,
+}
+
+/// Error returned from [`Gateway::shutdown`] when some channels were closed before all
+/// of their records were sent, or when some records were still expected to be received.
+#[derive(Debug, thiserror::Error)]
+#[error(
+    "gateway was shut down with unsent records (MPC channels: {mpc:?}, shard channels: {shard:?}) \
+    and records not received yet (MPC channels: {unreceived:?})"
+)]
+pub struct ShutdownError {
+    /// MPC send channels along with the records that were never sent through them.
+    pub mpc: Vec<(HelperChannelId, Range<RecordId>)>,
+    /// Shard send channels along with the records that were never sent through them.
+    pub shard: Vec<(ShardChannelId, Range<RecordId>)>,
+    /// MPC receive channels along with the records that were still expected to arrive.
+    pub unreceived: Vec<(HelperChannelId, Range<RecordId>)>,
+}
+
 #[derive(Clone, Copy, Debug)]
 pub struct GatewayConfig {
     /// The number of items that can be active at the one time.
@@ -104,6 +142,41 @@ pub struct GatewayConfig {
     /// send/receive requests
     #[cfg(feature = "stall-detection")]
     pub progress_check_interval: std::time::Duration,
+
+    /// Maximum time a single send is allowed to wait before its record is written to the send
+    /// buffer. This covers waiting for its turn behind records with lower ids as well as waiting
+    /// for capacity in the buffer. If a peer stops draining the channel, or earlier records are
+    /// not sent for longer than that, the send fails with [`Error::SendTimeout`] instead of
+    /// blocking forever. `None` disables the timeout.
+    ///
+    /// Records are written in order, so nothing after a timed out record can be sent either.
+    /// A timeout closes the channel: data buffered so far is still handed over to the network,
+    /// and all pending and later sends fail with [`Error::ChannelClosed`].
+    ///
+    /// Timeouts are not enforced when running under shuttle.
+    ///
+    /// [`Error::SendTimeout`]: crate::helpers::Error::SendTimeout
+    /// [`Error::ChannelClosed`]: crate::helpers::Error::ChannelClosed
+    pub send_timeout: Option<std::time::Duration>,
+
+    /// Time budget for the whole query, measured from the moment the gateway is created. Once
+    /// it is spent, every pending and future send, and every receive from an MPC peer, fails
+    /// with [`Error::DeadlineExceeded`]. `None` lets the query run for as long as it needs.
+    ///
+    /// The deadline is not enforced when running under shuttle, because shuttle does not
+    /// provide a clock.
+    ///
+    /// [`Error::DeadlineExceeded`]: crate::helpers::Error::DeadlineExceeded
+    pub query_deadline: Option<std::time::Duration>,
+
+    /// Simulated network latency. When set, every batch is held for this long after it is
+    /// taken from the send buffer before it is handed over to the transport, which makes it
+    /// possible to see how protocols behave over a slow network. `None` hands batches over as
+    /// soon as they are ready.
+    ///
+    /// Latency is not simulated when running under shuttle, because shuttle does not provide
+    /// a clock.
+    pub sink_latency: Option<std::time::Duration>,
 }
 
 impl ShardConfiguration for Gateway {
@@ -141,6 +214,7 @@ impl Gateway {
         Self {
             query_id,
             config,
+            deadline: config.query_deadline.map(|budget| Instant::now() + budget),
             transports: Transports {
                 mpc: RoleResolvingTransport {
                     roles,
@@ -157,6 +231,12 @@ impl Gateway {
         self.transports.mpc.identity()
     }
 
+    /// Reports the health of the connection to the helper `peer`. See [`Transport::peer_status`].
+    #[must_use]
+    pub fn peer_status(&self, peer: Role) -> PeerStatus {
+        self.transports.mpc.peer_status(peer)
+    }
+
     #[must_use]
     pub fn config(&self) -> &GatewayConfig {
         &self.config
@@ -177,8 +257,13 @@ impl Gateway {
     /// let mpc_channel = gateway.get_mpc_sender::<AdditiveShare<Fp32BitPrime>>(todo!(), todo!());
     /// ```
     ///
+    /// If `total_records` is unspecified, the channel is not opened and every send through it
+    /// fails with [`Error::TotalRecordsUnspecified`].
+    ///
     /// ## Panics
     /// If there is a failure connecting via HTTP
+    ///
+    /// [`Error::TotalRecordsUnspecified`]: crate::helpers::Error::TotalRecordsUnspecified
     #[must_use]
     pub fn get_mpc_sender<M: MpcMessage>(
         &self,
@@ -195,6 +280,7 @@ impl Gateway {
             self.config.set_active_work(active_work),
             self.query_id,
             total_records,
+            self.deadline,
         );
 
         send::SendingEnd::new(channel, transport.identity())
@@ -219,6 +305,7 @@ impl Gateway {
             self.config,
             self.query_id,
             total_records,
+            self.deadline,
         );
 
         send::SendingEnd::new(channel, transport.identity())
@@ -240,6 +327,7 @@ impl Gateway {
                     self.config.active_work(),
                 )
             }),
+            self.deadline,
         )
     }
 
@@ -269,6 +357,67 @@ impl Gateway {
             rx: RecordsStream::new(rx),
         }
     }
+
+    /// Lists every MPC channel that was opened through this gateway, along with the number
+    /// of records each has processed so far. Sending channels come first, and channels of
+    /// each kind are ordered by their id.
+    ///
+    /// Channels stay registered until the gateway is dropped, so this includes channels
+    /// that have already sent or received all of their records.
+    #[must_use]
+    pub fn active_channels(&self) -> Vec<ChannelSummary> {
+        let sending =
+            self.inner
+                .mpc_senders
+                .sent_records()
+                .into_iter()
+                .map(|(channel_id, records)| ChannelSummary {
+                    channel_id,
+                    kind: ChannelKind::Send,
+                    records,
+                });
+        let receiving =
+            self.inner
+                .mpc_receivers
+                .received_records()
+                .into_iter()
+                .map(|(channel_id, records)| ChannelSummary {
+                    channel_id,
+                    kind: ChannelKind::Receive,
+                    records,
+                });
+
+        sending.chain(receiving).collect()
+    }
+
+    /// Shuts down this gateway. Every channel that is still open is closed right after the
+    /// last record written into it, so data that is buffered but not yet handed to the
+    /// network (a partial batch) is flushed to the peer.
+    ///
+    /// Sends that are still in flight, and any sends issued after this, fail with
+    /// [`Error::ChannelClosed`]. Peers that are still waiting for records on closed channels
+    /// will see end of stream.
+    ///
+    /// ## Errors
+    /// If some channels expected more records than were sent through them, or if some
+    /// receives are still waiting for records. The error lists these channels along with
+    /// the records that were never sent or received.
+    ///
+    /// [`Error::ChannelClosed`]: crate::helpers::Error::ChannelClosed
+    pub fn shutdown(&self) -> Result<(), ShutdownError> {
+        let unreceived = self.inner.mpc_receivers.expected_records();
+        let mpc = self.inner.mpc_senders.shutdown();
+        let shard = self.inner.shard_senders.shutdown();
+        if mpc.is_empty() && shard.is_empty() && unreceived.is_empty() {
+            Ok(())
+        } else {
+            Err(ShutdownError {
+                mpc,
+                shard,
+                unreceived,
+            })
+        }
+    }
 }
 
 impl Default for GatewayConfig {
@@ -286,6 +435,9 @@ impl Default for GatewayConfig {
             } else {
                 30
             }),
+            send_timeout: None,
+            query_deadline: None,
+            sink_latency: None,
         }
     }
 }
@@ -338,11 +490,13 @@ impl GatewayConfig {
 mod tests {
     use std::{
         iter::{repeat, zip},
+        pin::pin,
         sync::Arc,
+        time::{Duration, Instant},
     };
 
     use futures::{
-        future::{join, try_join, try_join_all},
+        future::{join, poll_immediate, try_join, try_join_all},
         stream,
         stream::StreamExt,
     };
@@ -357,18 +511,23 @@ mod tests {
         helpers::{
             gateway::QueryConfig,
             query::{QuerySize, QueryType},
-            ChannelId, Direction, GatewayConfig, MpcMessage, MpcReceivingEnd, Role, SendingEnd,
-            TotalRecords,
+            ChannelId, ChannelKind, ChannelSummary, Direction, Error, Gateway, GatewayConfig,
+            HelperIdentity, InMemoryMpcNetwork, InMemoryShardNetwork, MpcMessage, MpcReceivingEnd,
+            Role, RoleAssignment, SendingEnd, TotalRecords,
         },
         protocol::{
             context::{Context, ShardedContext},
-            Gate, RecordId,
+            Gate, QueryId, RecordId,
         },
         secret_sharing::{
             replicated::semi_honest::AdditiveShare, SharedValue, SharedValueArray, StdArray,
         },
         seq_join::seq_join,
-        sharding::ShardConfiguration,
+        sharding::{ShardConfiguration, ShardIndex},
+        telemetry::{
+            labels::{FILL, FLUSH},
+            metrics::{BYTES_SENT, RECORDS_SENT, SEND_BUFFER_FILL_RATIO, SEND_BUFFER_FLUSHES},
+        },
         test_executor::run,
         test_fixture::{Reconstruct, Runner, TestWorld, TestWorldConfig, WithShards},
         utils::NonZeroU32PowerOfTwo,
@@ -424,17 +583,54 @@ mod tests {
             },
             ..TestWorldConfig::default()
         };
+        check_reordering(config, Role::H1).await;
+    }
+
+    /// Helpers may be configured differently. H2 flushes every record, while H1 and H3 batch
+    /// them, and reordered records must still arrive intact in every direction.
+    #[tokio::test]
+    async fn handles_reordering_asymmetric_batches() {
+        let gateway_config = GatewayConfig {
+            active: 2.try_into().unwrap(),
+            ..Default::default()
+        };
+        let config = TestWorldConfig {
+            gateway_config,
+            per_role_gateway: [
+                None,
+                Some(GatewayConfig {
+                    read_size: 1.try_into().unwrap(),
+                    ..gateway_config
+                }),
+                None,
+            ],
+            ..TestWorldConfig::default()
+        };
+        assert_eq!(1, config.gateway_config(Role::H2).read_size.get());
+        assert!(config.gateway_config(Role::H1).read_size.get() > 1);
+
+        for sender in Role::all() {
+            check_reordering(config.clone(), *sender).await;
+        }
+    }
+
+    async fn check_reordering(config: TestWorldConfig, sender: Role) {
+        let receiver = sender.peer(Direction::Right);
         let world = Box::leak(Box::new(TestWorld::new_with(config)));
         let world_ptr = world as *mut _;
         let contexts = world.contexts();
-        let sender_ctx = contexts[0].narrow("reordering-test").set_total_records(2);
-        let recv_ctx = contexts[1].narrow("reordering-test").set_total_records(2);
+        let sender_ctx = contexts[sender]
+            .narrow("reordering-test")
+            .set_total_records(2);
+        let recv_ctx = contexts[receiver]
+            .narrow("reordering-test")
+            .set_total_records(2);
 
         // send record 1 first and wait for confirmation before sending record 0.
         // when gateway received record 0 it triggers flush so it must make sure record 1 is also
         // sent (same batch or different does not matter here)
         let spawned = tokio::spawn(async move {
-            let channel = sender_ctx.send_channel(Role::H2);
+            let channel = sender_ctx.send_channel(receiver);
             try_join(
                 channel.send(RecordId::from(1), Fp31::truncate_from(1_u128)),
                 channel.send(RecordId::from(0), Fp31::truncate_from(0_u128)),
@@ -443,7 +639,7 @@ mod tests {
             .unwrap();
         });
 
-        let recv_channel = recv_ctx.recv_channel::<Fp31>(Role::H1);
+        let recv_channel = recv_ctx.recv_channel::<Fp31>(sender);
         let result = try_join(
             recv_channel.receive(RecordId::from(1)),
             recv_channel.receive(RecordId::from(0)),
@@ -459,6 +655,72 @@ mod tests {
         let _world = unsafe { Box::from_raw(world_ptr) };
     }
 
+    /// Channel ends don't borrow the context that created them, so they can be moved into
+    /// spawned tasks.
+    #[tokio::test]
+    async fn send_from_spawned_task() {
+        let world = TestWorld::default();
+        world
+            .semi_honest((), |ctx, ()| async move {
+                let ctx = ctx.set_total_records(1);
+                let role = ctx.role();
+                let send_channel = ctx.send_channel::<Fp31>(role.peer(Direction::Right));
+                let recv_channel = ctx.recv_channel::<Fp31>(role.peer(Direction::Left));
+
+                let sent = tokio::spawn(async move {
+                    send_channel
+                        .send(RecordId::FIRST, Fp31::truncate_from(role as u128))
+                        .await
+                        .unwrap();
+                });
+                let received = recv_channel.receive(RecordId::FIRST).await.unwrap();
+                sent.await.unwrap();
+
+                assert_eq!(
+                    Fp31::truncate_from(role.peer(Direction::Left) as u128),
+                    received
+                );
+            })
+            .await;
+    }
+
+    /// Messages that carry several field values per record are sent and received as a single
+    /// record.
+    #[test]
+    fn send_field_array() {
+        run(|| async {
+            let world = TestWorld::default();
+            let values = [1_u128, 17, 30].map(Fp31::truncate_from);
+            let sent = StdArray::<Fp31, 3>::try_from(values.to_vec()).unwrap();
+
+            let received = world
+                .semi_honest((), |ctx, ()| {
+                    let sent = sent.clone();
+                    async move {
+                        let ctx = ctx.set_total_records(1);
+                        let (left, right) = (
+                            ctx.role().peer(Direction::Left),
+                            ctx.role().peer(Direction::Right),
+                        );
+                        let send_channel = ctx.send_channel::<StdArray<Fp31, 3>>(right);
+                        let recv_channel = ctx.recv_channel::<StdArray<Fp31, 3>>(left);
+
+                        let ((), received) = try_join(
+                            send_channel.send(RecordId::FIRST, sent),
+                            recv_channel.receive(RecordId::FIRST),
+                        )
+                        .await
+                        .unwrap();
+
+                        received
+                    }
+                })
+                .await;
+
+            assert_eq!(received, [sent.clone(), sent.clone(), sent]);
+        });
+    }
+
     /// this test requires quite a few threads to simulate send contention and will panic if
     /// there is more than one sender channel created per step.
     #[tokio::test(flavor = "multi_thread", worker_threads = 20)]
@@ -628,6 +890,739 @@ mod tests {
         });
     }
 
+    /// Records that are sent, but never claimed on the receiving side, must not pile up
+    /// without bound. [`UnorderedReceiver`] only pulls data from the network
+    /// when the next record is requested, so the sender is expected to block once it fills
+    /// up its buffer of `active` records, and resume once the receiver starts draining.
+    ///
+    /// [`UnorderedReceiver`]: crate::helpers::buffers::UnorderedReceiver
+    #[test]
+    fn unclaimed_records_block_sender() {
+        run(|| async move {
+            let world = TestWorld::new_with(TestWorldConfig {
+                gateway_config: GatewayConfig {
+                    active: 2.try_into().unwrap(),
+                    ..Default::default()
+                },
+                ..Default::default()
+            });
+            let active_work = world
+                .gateway(Role::H1)
+                .config()
+                .active_work_as_power_of_two();
+            let sender = world.gateway(Role::H1).get_mpc_sender::<Fp31>(
+                &ChannelId::new(Role::H2, Gate::default()),
+                TotalRecords::specified(10).unwrap(),
+                active_work,
+            );
+            let recv = world
+                .gateway(Role::H2)
+                .get_mpc_receiver::<Fp31>(&ChannelId::new(Role::H1, Gate::default()));
+
+            for record_id in 0..active_work.get() {
+                sender
+                    .send(
+                        record_id.into(),
+                        Fp31::truncate_from(u128::try_from(record_id).unwrap()),
+                    )
+                    .await
+                    .unwrap();
+            }
+
+            let mut blocked = pin!(sender.send(active_work.get().into(), Fp31::ZERO));
+            for _ in 0..10 {
+                assert!(poll_immediate(&mut blocked).await.is_none());
+                tokio::task::yield_now().await;
+            }
+
+            assert_eq!(
+                Fp31::truncate_from(0_u128),
+                recv.receive(RecordId::FIRST).await.unwrap()
+            );
+            blocked.await.unwrap();
+        });
+    }
+
+    #[test]
+    fn active_channels() {
+        run(|| async move {
+            let world = TestWorld::default();
+            let active_work = world
+                .gateway(Role::H1)
+                .config()
+                .active_work_as_power_of_two();
+            let gate_a = Gate::from("a");
+            let gate_b = Gate::from("b");
+            let send_a = world.gateway(Role::H1).get_mpc_sender::<Fp31>(
+                &ChannelId::new(Role::H2, gate_a.clone()),
+                TotalRecords::specified(2).unwrap(),
+                active_work,
+            );
+            let send_b = world.gateway(Role::H1).get_mpc_sender::<Fp31>(
+                &ChannelId::new(Role::H2, gate_b.clone()),
+                TotalRecords::specified(3).unwrap(),
+                active_work,
+            );
+            let recv_a = world
+                .gateway(Role::H2)
+                .get_mpc_receiver::<Fp31>(&ChannelId::new(Role::H1, gate_a.clone()));
+            let recv_b = world
+                .gateway(Role::H2)
+                .get_mpc_receiver::<Fp31>(&ChannelId::new(Role::H1, gate_b.clone()));
+
+            try_join_all((0..2).map(|i| send_a.send(i.into(), Fp31::ZERO)))
+                .await
+                .unwrap();
+            try_join_all((0..3).map(|i| send_b.send(i.into(), Fp31::ZERO)))
+                .await
+                .unwrap();
+            recv_a
+                .receive_many(RecordId::FIRST..2.into())
+                .await
+                .unwrap();
+            recv_b.receive(RecordId::FIRST).await.unwrap();
+
+            let summary = |peer, gate: &Gate, kind, records| ChannelSummary {
+                channel_id: ChannelId::new(peer, gate.clone()),
+                kind,
+                records,
+            };
+            assert_eq!(
+                vec![
+                    summary(Role::H2, &gate_a, ChannelKind::Send, 2),
+                    summary(Role::H2, &gate_b, ChannelKind::Send, 3),
+                ],
+                world.gateway(Role::H1).active_channels()
+            );
+            assert_eq!(
+                vec![
+                    summary(Role::H1, &gate_a, ChannelKind::Receive, 2),
+                    summary(Role::H1, &gate_b, ChannelKind::Receive, 1),
+                ],
+                world.gateway(Role::H2).active_channels()
+            );
+            assert!(world.gateway(Role::H3).active_channels().is_empty());
+        });
+    }
+
+    /// Closing a channel does not count as sending a record.
+    #[test]
+    fn active_channels_after_close() {
+        run(|| async move {
+            let world = TestWorld::default();
+            let channel_id = ChannelId::new(Role::H2, Gate::default());
+            let sender = world.gateway(Role::H1).get_mpc_sender::<Fp31>(
+                &channel_id,
+                TotalRecords::Indeterminate,
+                world
+                    .gateway(Role::H1)
+                    .config()
+                    .active_work_as_power_of_two(),
+            );
+
+            try_join_all((0..2).map(|i| sender.send(i.into(), Fp31::ZERO)))
+                .await
+                .unwrap();
+            sender.close(RecordId::from(2)).await;
+
+            assert_eq!(
+                vec![ChannelSummary {
+                    channel_id,
+                    kind: ChannelKind::Send,
+                    records: 2,
+                }],
+                world.gateway(Role::H1).active_channels()
+            );
+        });
+    }
+
+    #[test]
+    fn try_receive() {
+        run(|| async move {
+            let world = TestWorld::default();
+            let sender = world.gateway(Role::H1).get_mpc_sender::<Fp31>(
+                &ChannelId::new(Role::H2, Gate::default()),
+                TotalRecords::specified(2).unwrap(),
+                world
+                    .gateway(Role::H1)
+                    .config()
+                    .active_work_as_power_of_two(),
+            );
+            let recv = world
+                .gateway(Role::H2)
+                .get_mpc_receiver::<Fp31>(&ChannelId::new(Role::H1, Gate::default()));
+
+            assert_eq!(None, recv.try_receive(RecordId::FIRST).unwrap());
+
+            sender
+                .send(RecordId::FIRST, Fp31::truncate_from(0_u128))
+                .await
+                .unwrap();
+            sender
+                .send(RecordId::from(1), Fp31::truncate_from(1_u128))
+                .await
+                .unwrap();
+            // record 1 can't be read until record 0 is received
+            assert_eq!(None, recv.try_receive(RecordId::from(1)).unwrap());
+
+            let v = loop {
+                if let Some(v) = recv.try_receive(RecordId::FIRST).unwrap() {
+                    break v;
+                }
+                tokio::task::yield_now().await;
+            };
+            assert_eq!(Fp31::truncate_from(0_u128), v);
+            assert_eq!(
+                Fp31::truncate_from(1_u128),
+                recv.receive(RecordId::from(1)).await.unwrap()
+            );
+        });
+    }
+
+    #[test]
+    fn receive_many() {
+        run(|| async move {
+            const TOTAL_RECORDS: usize = 10;
+            let world = TestWorld::default();
+            let sender = world.gateway(Role::H1).get_mpc_sender::<Fp31>(
+                &ChannelId::new(Role::H2, Gate::default()),
+                TotalRecords::specified(TOTAL_RECORDS).unwrap(),
+                world
+                    .gateway(Role::H1)
+                    .config()
+                    .active_work_as_power_of_two(),
+            );
+            let recv = world
+                .gateway(Role::H2)
+                .get_mpc_receiver::<Fp31>(&ChannelId::new(Role::H1, Gate::default()));
+
+            let (_, received) = try_join(
+                try_join_all((0..TOTAL_RECORDS).rev().map(|i| {
+                    sender.send(
+                        RecordId::from(i),
+                        Fp31::truncate_from(u128::try_from(i).unwrap()),
+                    )
+                })),
+                recv.receive_many(RecordId::FIRST..RecordId::from(TOTAL_RECORDS)),
+            )
+            .await
+            .unwrap();
+
+            assert_eq!(
+                (0..TOTAL_RECORDS)
+                    .map(|i| Fp31::truncate_from(u128::try_from(i).unwrap()))
+                    .collect::<Vec<_>>(),
+                received
+            );
+        });
+    }
+
+    /// Creates gateways that are not owned by a [`TestWorld`], so they can be shut down.
+    fn standalone_gateways(
+        config: GatewayConfig,
+    ) -> (InMemoryMpcNetwork, InMemoryShardNetwork, [Gateway; 3]) {
+        let mpc_network = InMemoryMpcNetwork::default();
+        let shard_network = InMemoryShardNetwork::with_shards(1);
+        let gateways = HelperIdentity::make_three().map(|id| {
+            Gateway::new(
+                QueryId,
+                config,
+                RoleAssignment::new(HelperIdentity::make_three()),
+                mpc_network.transport(id),
+                shard_network.transport(id, ShardIndex::FIRST),
+            )
+        });
+
+        (mpc_network, shard_network, gateways)
+    }
+
+    #[test]
+    fn shutdown_flushes_partial_batch() {
+        run(|| async move {
+            let config = GatewayConfig {
+                active: 16.try_into().unwrap(),
+                read_size: 16.try_into().unwrap(),
+                ..Default::default()
+            };
+            let (_mpc, _shard, [h1, h2, _]) = standalone_gateways(config);
+            let sender = h1.get_mpc_sender::<Fp31>(
+                &ChannelId::new(Role::H2, Gate::default()),
+                TotalRecords::specified(10).unwrap(),
+                config.active_work_as_power_of_two(),
+            );
+            let recv = h2.get_mpc_receiver::<Fp31>(&ChannelId::new(Role::H1, Gate::default()));
+            // One record does not fill up the read size, so it stays in the send buffer
+            sender
+                .send(RecordId::FIRST, Fp31::truncate_from(5_u128))
+                .await
+                .unwrap();
+            assert_eq!(None, recv.try_receive(RecordId::FIRST).unwrap());
+
+            let err = h1.shutdown().unwrap_err();
+            assert_eq!(
+                vec![(
+                    ChannelId::new(Role::H2, Gate::default()),
+                    RecordId::from(1)..RecordId::from(10)
+                )],
+                err.mpc
+            );
+            assert!(err.shard.is_empty());
+            assert!(err.unreceived.is_empty());
+
+            assert_eq!(
+                Fp31::truncate_from(5_u128),
+                recv.receive(RecordId::FIRST).await.unwrap()
+            );
+            assert!(matches!(
+                recv.receive(RecordId::from(1)).await,
+                Err(Error::EndOfStream { .. })
+            ));
+        });
+    }
+
+    /// Sends that race with shutdown fail instead of panicking, and receives that are still
+    /// waiting for records are reported.
+    #[test]
+    fn shutdown_fails_pending_sends() {
+        run(|| async move {
+            let config = GatewayConfig::default();
+            let (_mpc, _shard, [h1, _, _]) = standalone_gateways(config);
+            let send_id = ChannelId::new(Role::H2, Gate::default());
+            let recv_id = ChannelId::new(Role::H3, Gate::default());
+            let sender = h1.get_mpc_sender::<Fp31>(
+                &send_id,
+                TotalRecords::specified(10).unwrap(),
+                config.active_work_as_power_of_two(),
+            );
+            let recv = h1.get_mpc_receiver::<Fp31>(&recv_id);
+
+            sender.send(RecordId::FIRST, Fp31::ZERO).await.unwrap();
+            // record 1 is not sent yet, so this one waits for its turn
+            let mut in_flight = pin!(sender.send(RecordId::from(2), Fp31::ZERO));
+            assert!(poll_immediate(&mut in_flight).await.is_none());
+            let mut pending_recv = pin!(recv.receive(RecordId::from(3)));
+            assert!(poll_immediate(&mut pending_recv).await.is_none());
+
+            let err = h1.shutdown().unwrap_err();
+            assert_eq!(
+                vec![(send_id.clone(), RecordId::from(1)..RecordId::from(10))],
+                err.mpc
+            );
+            assert_eq!(
+                vec![(recv_id, RecordId::FIRST..RecordId::from(4))],
+                err.unreceived
+            );
+
+            for result in [
+                in_flight.await,
+                sender.send(RecordId::from(1), Fp31::ZERO).await,
+            ] {
+                assert!(
+                    matches!(&result, Err(Error::ChannelClosed { channel_id }) if channel_id == &send_id),
+                    "{result:?}"
+                );
+            }
+        });
+    }
+
+    #[test]
+    fn shutdown_completed() {
+        run(|| async move {
+            let config = GatewayConfig::default();
+            let (_mpc, _shard, [h1, h2, _]) = standalone_gateways(config);
+            let sender = h1.get_mpc_sender::<Fp31>(
+                &ChannelId::new(Role::H2, Gate::default()),
+                TotalRecords::specified(1).unwrap(),
+                config.active_work_as_power_of_two(),
+            );
+            sender.send(RecordId::FIRST, Fp31::ZERO).await.unwrap();
+            h1.shutdown().unwrap();
+            assert_eq!(
+                Fp31::ZERO,
+                h2.get_mpc_receiver::<Fp31>(&ChannelId::new(Role::H1, Gate::default()))
+                    .receive(RecordId::FIRST)
+                    .await
+                    .unwrap()
+            );
+        });
+    }
+
+    #[test]
+    fn finalize_indeterminate() {
+        run(|| async move {
+            let config = GatewayConfig {
+                active: 16.try_into().unwrap(),
+                read_size: 16.try_into().unwrap(),
+                ..Default::default()
+            };
+            let (_mpc, _shard, [h1, h2, _]) = standalone_gateways(config);
+            let sender = h1.get_mpc_sender::<Fp31>(
+                &ChannelId::new(Role::H2, Gate::default()),
+                TotalRecords::Indeterminate,
+                config.active_work_as_power_of_two(),
+            );
+            let recv = h2.get_mpc_receiver::<Fp31>(&ChannelId::new(Role::H1, Gate::default()));
+
+            // records are handed over one by one, until the number of records is known
+            for i in 0..3_u32 {
+                sender
+                    .send(RecordId::from(i), Fp31::truncate_from(i))
+                    .await
+                    .unwrap();
+                assert_eq!(
+                    Fp31::truncate_from(i),
+                    recv.receive(RecordId::from(i)).await.unwrap()
+                );
+            }
+
+            sender.finalize_total_records(8).await;
+            sender
+                .send(RecordId::from(3), Fp31::truncate_from(3_u128))
+                .await
+                .unwrap();
+            // now it is batched, and the remaining records don't fill up a batch
+            assert_eq!(None, recv.try_receive(RecordId::from(3)).unwrap());
+
+            for i in 4..8_u32 {
+                sender
+                    .send(RecordId::from(i), Fp31::truncate_from(i))
+                    .await
+                    .unwrap();
+            }
+            assert_eq!(
+                (3..8_u32).map(Fp31::truncate_from).collect::<Vec<_>>(),
+                recv.receive_many(RecordId::from(3)..RecordId::from(8))
+                    .await
+                    .unwrap()
+            );
+            assert!(matches!(
+                recv.receive(RecordId::from(8)).await,
+                Err(Error::EndOfStream { .. })
+            ));
+        });
+    }
+
+    #[test]
+    fn finalize_indeterminate_after_last_record() {
+        run(|| async move {
+            let config = GatewayConfig::default();
+            let (_mpc, _shard, [h1, h2, _]) = standalone_gateways(config);
+            let sender = h1.get_mpc_sender::<Fp31>(
+                &ChannelId::new(Role::H2, Gate::default()),
+                TotalRecords::Indeterminate,
+                config.active_work_as_power_of_two(),
+            );
+            let recv = h2.get_mpc_receiver::<Fp31>(&ChannelId::new(Role::H1, Gate::default()));
+            for i in 0..2_u32 {
+                sender
+                    .send(RecordId::from(i), Fp31::truncate_from(i))
+                    .await
+                    .unwrap();
+            }
+
+            // all records are sent, so the channel must be closed right away
+            sender.finalize_total_records(2).await;
+            assert_eq!(
+                vec![Fp31::ZERO, Fp31::truncate_from(1_u128)],
+                recv.receive_many(RecordId::FIRST..RecordId::from(2))
+                    .await
+                    .unwrap()
+            );
+            assert!(matches!(
+                recv.receive(RecordId::from(2)).await,
+                Err(Error::EndOfStream { .. })
+            ));
+        });
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "only channels with indeterminate number of records can be finalized"
+    )]
+    fn finalize_specified() {
+        run(|| async move {
+            let config = GatewayConfig::default();
+            let (_mpc, _shard, [h1, _, _]) = standalone_gateways(config);
+            h1.get_mpc_sender::<Fp31>(
+                &ChannelId::new(Role::H2, Gate::default()),
+                TotalRecords::specified(2).unwrap(),
+                config.active_work_as_power_of_two(),
+            )
+            .finalize_total_records(2)
+            .await;
+        });
+    }
+
+    #[test]
+    fn send_unspecified_total_records() {
+        run(|| async move {
+            let config = GatewayConfig::default();
+            let (_mpc, _shard, [h1, _, _]) = standalone_gateways(config);
+            let channel_id = ChannelId::new(Role::H2, Gate::default());
+            let sender = h1.get_mpc_sender::<Fp31>(
+                &channel_id,
+                TotalRecords::Unspecified,
+                config.active_work_as_power_of_two(),
+            );
+            let err = poll_immediate(sender.send(RecordId::FIRST, Fp31::truncate_from(1_u128)))
+                .await
+                .expect("send must fail immediately")
+                .unwrap_err();
+            assert!(
+                matches!(&err, Error::TotalRecordsUnspecified { channel_id: c } if c == &channel_id),
+                "{err:?}"
+            );
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "is already used to send a different message type")]
+    fn send_different_message_types() {
+        run(|| async move {
+            let config = GatewayConfig::default();
+            let (_mpc, _shard, [h1, _, _]) = standalone_gateways(config);
+            let channel_id = ChannelId::new(Role::H2, Gate::default());
+            let _fp31 = h1.get_mpc_sender::<Fp31>(
+                &channel_id,
+                TotalRecords::ONE,
+                config.active_work_as_power_of_two(),
+            );
+            // same record size, but a protocol reading this channel expects `Fp31`
+            let _ba8 = h1.get_mpc_sender::<BA8>(
+                &channel_id,
+                TotalRecords::ONE,
+                config.active_work_as_power_of_two(),
+            );
+        });
+    }
+
+    #[test]
+    fn send_buffer_flush_metrics() {
+        run(|| async move {
+            const TOTAL_RECORDS: usize = 10;
+            let world = TestWorld::new_with(TestWorldConfig {
+                gateway_config: GatewayConfig {
+                    active: 8.try_into().unwrap(),
+                    // 4 Fp31 values per batch
+                    read_size: 4.try_into().unwrap(),
+                    ..Default::default()
+                },
+                ..TestWorldConfig::default().enable_metrics()
+            });
+            world
+                .semi_honest((), |ctx, ()| async move {
+                    let ctx = ctx.set_total_records(TOTAL_RECORDS);
+                    let send_channel = ctx.send_channel::<Fp31>(ctx.role().peer(Direction::Right));
+                    let recv_channel = ctx.recv_channel::<Fp31>(ctx.role().peer(Direction::Left));
+                    try_join(
+                        try_join_all(
+                            (0..TOTAL_RECORDS)
+                                .map(|i| send_channel.send(RecordId::from(i), Fp31::ZERO)),
+                        ),
+                        try_join_all(
+                            (0..TOTAL_RECORDS).map(|i| recv_channel.receive(RecordId::from(i))),
+                        ),
+                    )
+                    .await
+                    .unwrap();
+                })
+                .await;
+
+            // every helper flushes two full batches and one partial batch with the last 2 records
+            let snapshot = world.metrics_snapshot();
+            let flushes = snapshot
+                .assert_metric(SEND_BUFFER_FLUSHES)
+                .total(9)
+                .per_dimension(FLUSH, "full", 6)
+                .per_dimension(FLUSH, "forced", 3);
+            for role in Role::all() {
+                flushes.per_helper(role, 3);
+            }
+            snapshot
+                .assert_metric(SEND_BUFFER_FILL_RATIO)
+                .total(9)
+                .per_dimension(FILL, "100", 6)
+                .per_dimension(FILL, "50", 3);
+        });
+    }
+
+    #[test]
+    fn records_sent_metrics() {
+        run(|| async move {
+            let world = TestWorld::new_with(TestWorldConfig::default().enable_metrics());
+            world
+                .semi_honest((), |ctx, ()| async move {
+                    let ctx = ctx.set_total_records(3);
+                    match ctx.role() {
+                        Role::H1 => {
+                            let send_channel = ctx.send_channel::<Fp32BitPrime>(Role::H2);
+                            try_join_all(
+                                (0..3_usize).map(|i| {
+                                    send_channel.send(RecordId::from(i), Fp32BitPrime::ZERO)
+                                }),
+                            )
+                            .await
+                            .unwrap();
+                        }
+                        Role::H2 => {
+                            let recv_channel = ctx.recv_channel::<Fp32BitPrime>(Role::H1);
+                            try_join_all(
+                                (0..3_usize).map(|i| recv_channel.receive(RecordId::from(i))),
+                            )
+                            .await
+                            .unwrap();
+                        }
+                        Role::H3 => {}
+                    }
+                })
+                .await;
+
+            let snapshot = world.metrics_snapshot();
+            snapshot
+                .assert_metric(RECORDS_SENT)
+                .total(3)
+                .per_helper(&Role::H1, 3);
+            snapshot
+                .assert_metric(BYTES_SENT)
+                .total(12)
+                .per_helper(&Role::H1, 12);
+        });
+    }
+
+    #[test]
+    fn send_timeout() {
+        const TIMEOUT: Duration = Duration::from_millis(100);
+
+        run(|| async move {
+            let world = TestWorld::new_with(TestWorldConfig {
+                gateway_config: GatewayConfig {
+                    active: 2.try_into().unwrap(),
+                    send_timeout: Some(TIMEOUT),
+                    ..Default::default()
+                },
+                ..Default::default()
+            });
+            let channel_id = ChannelId::new(Role::H2, Gate::default());
+            let sender = world.gateway(Role::H1).get_mpc_sender::<Fp31>(
+                &channel_id,
+                TotalRecords::specified(10).unwrap(),
+                world
+                    .gateway(Role::H1)
+                    .config()
+                    .active_work_as_power_of_two(),
+            );
+
+            // H2 never receives, so the third record cannot fit into the send buffer.
+            sender.send(RecordId::from(0), Fp31::ZERO).await.unwrap();
+            sender.send(RecordId::from(1), Fp31::ZERO).await.unwrap();
+            let start = Instant::now();
+            let err = sender
+                .send(RecordId::from(2), Fp31::ZERO)
+                .await
+                .unwrap_err();
+            let measured = start.elapsed();
+            assert!(measured >= TIMEOUT);
+            assert!(
+                matches!(
+                    &err,
+                    Error::SendTimeout { channel_id: c, record_id, elapsed }
+                        if c == &channel_id
+                            && *record_id == RecordId::from(2)
+                            && (TIMEOUT..=measured).contains(elapsed)
+                ),
+                "unexpected error: {err:?}"
+            );
+
+            // record 2 is never written, so the channel is closed instead of stalling
+            let start = Instant::now();
+            let err = sender
+                .send(RecordId::from(3), Fp31::ZERO)
+                .await
+                .unwrap_err();
+            assert!(start.elapsed() < TIMEOUT);
+            assert!(
+                matches!(&err, Error::ChannelClosed { channel_id: c } if c == &channel_id),
+                "unexpected error: {err:?}"
+            );
+        });
+    }
+
+    #[test]
+    fn query_deadline() {
+        const DEADLINE: Duration = Duration::from_millis(100);
+
+        run(|| async move {
+            let world = TestWorld::new_with(TestWorldConfig {
+                gateway_config: GatewayConfig {
+                    query_deadline: Some(DEADLINE),
+                    ..Default::default()
+                },
+                ..Default::default()
+            });
+            let channel_id = ChannelId::new(Role::H1, Gate::default());
+            let receiver = world
+                .gateway(Role::H2)
+                .get_mpc_receiver::<Fp31>(&channel_id);
+
+            // H1 never sends, so this receive can only be resolved by the deadline
+            let err = receiver.receive(RecordId::FIRST).await.unwrap_err();
+            assert!(
+                matches!(
+                    &err,
+                    Error::DeadlineExceeded { channel_id: c, record_id }
+                        if c == &channel_id && *record_id == RecordId::FIRST
+                ),
+                "unexpected error: {err:?}"
+            );
+
+            // once the budget is spent, sends fail too
+            let err = world
+                .gateway(Role::H1)
+                .get_mpc_sender::<Fp31>(
+                    &ChannelId::new(Role::H2, Gate::default()),
+                    TotalRecords::ONE,
+                    world
+                        .gateway(Role::H1)
+                        .config()
+                        .active_work_as_power_of_two(),
+                )
+                .send(RecordId::FIRST, Fp31::ZERO)
+                .await
+                .unwrap_err();
+            assert!(
+                matches!(err, Error::DeadlineExceeded { .. }),
+                "unexpected error: {err:?}"
+            );
+        });
+    }
+
+    /// Records sent over a slow network still arrive intact and in order, just later.
+    #[tokio::test]
+    async fn sink_latency() {
+        const LATENCY: Duration = Duration::from_millis(100);
+
+        async fn check_all(sink_latency: Option<Duration>) -> Duration {
+            let config = TestWorldConfig {
+                gateway_config: GatewayConfig {
+                    sink_latency,
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            let start = Instant::now();
+            for sender in Role::all() {
+                check_reordering(config.clone(), *sender).await;
+            }
+            start.elapsed()
+        }
+
+        let baseline = check_all(None).await;
+        let slow = check_all(Some(LATENCY)).await;
+
+        assert!(slow >= 3 * LATENCY, "{slow:?} is below {:?}", 3 * LATENCY);
+        assert!(slow > baseline, "{slow:?} is not slower than {baseline:?}");
+    }
+
     macro_rules! send_recv_test {
         (
             message: $message:expr,
@@ -1,7 +1,10 @@
 use std::{
+    future::Future,
     marker::PhantomData,
+    ops::Range,
     pin::Pin,
     task::{Context, Poll},
+    time::Instant,
 };
 
 use bytes::Bytes;
@@ -31,6 +34,7 @@ use crate::{
 pub struct MpcReceivingEnd<M> {
     channel_id: HelperChannelId,
     unordered_rx: UR,
+    deadline: Option<Instant>,
     _phantom: PhantomData<fn() -> M>,
 }
 
@@ -62,14 +66,42 @@ pub struct ShardReceiveStream(
 );
 
 impl<M: MpcMessage> MpcReceivingEnd<M> {
-    pub(super) fn new(channel_id: HelperChannelId, rx: UR) -> Self {
+    pub(super) fn new(channel_id: HelperChannelId, rx: UR, deadline: Option<Instant>) -> Self {
         Self {
             channel_id,
             unordered_rx: rx,
+            deadline,
             _phantom: PhantomData,
         }
     }
 
+    /// Fails with [`Error::DeadlineExceeded`] if `fut` does not complete before the query
+    /// deadline.
+    #[cfg_attr(feature = "shuttle", allow(unused_variables))]
+    async fn before_deadline<T, F: Future<Output = Result<T, Error<Role>>>>(
+        &self,
+        record_id: RecordId,
+        fut: F,
+    ) -> Result<T, Error<Role>> {
+        match self.deadline {
+            #[cfg(not(feature = "shuttle"))]
+            Some(deadline) => {
+                let expired = || Error::DeadlineExceeded {
+                    channel_id: self.channel_id.clone(),
+                    record_id,
+                };
+                // records that have already arrived are not handed out either
+                if Instant::now() >= deadline {
+                    return Err(expired());
+                }
+                ::tokio::time::timeout_at(deadline.into(), fut)
+                    .await
+                    .map_err(|_| expired())?
+            }
+            _ => fut.await,
+        }
+    }
+
     /// Receive message associated with the given record id. This method does not return until
     /// message is actually received and deserialized.
     ///
@@ -81,19 +113,75 @@ impl<M: MpcMessage> MpcReceivingEnd<M> {
     /// and sent to this helper.
     #[tracing::instrument(level = "trace", "receive", skip_all, fields(i = %record_id, from = ?self.channel_id.peer, gate = ?self.channel_id.gate.as_ref()))]
     pub async fn receive(&self, record_id: RecordId) -> Result<M, Error<Role>> {
+        self.before_deadline(record_id, async {
+            self.unordered_rx
+                .recv::<M, _>(record_id)
+                .await
+                .map_err(|e| self.map_err(e))
+        })
+        .await
+    }
+
+    /// Receive messages for all records in the given range. This is equivalent to calling
+    /// [`receive`] for every record in it, but it is cheaper as only one future is created
+    /// for the whole range.
+    ///
+    /// Unlike [`receive`], this is not cancel-safe. Records received before the future is
+    /// dropped are lost, and requesting them again panics. Once [`GatewayConfig::query_deadline`]
+    /// runs out, every receive fails with [`Error::DeadlineExceeded`] before reaching the
+    /// channel, so the deadline cannot trigger that panic.
+    ///
+    /// ## Errors
+    /// Returns an error if receiving any of the records fails
+    ///
+    /// ## Panics
+    /// If any record in the range was already requested via [`receive`] or another
+    /// `receive_many` call, including one that was dropped before it completed.
+    ///
+    /// [`receive`]: Self::receive
+    /// [`GatewayConfig::query_deadline`]: crate::helpers::GatewayConfig::query_deadline
+    #[tracing::instrument(level = "trace", "receive_many", skip_all, fields(records = ?records, from = ?self.channel_id.peer, gate = ?self.channel_id.gate.as_ref()))]
+    pub async fn receive_many(&self, records: Range<RecordId>) -> Result<Vec<M>, Error<Role>> {
+        self.before_deadline(records.start, async {
+            self.unordered_rx
+                .recv_many::<M>(usize::from(records.start)..usize::from(records.end))
+                .await
+                .map_err(|e| self.map_err(e))
+        })
+        .await
+    }
+
+    /// Receive message associated with the given record id, if it is available right now.
+    /// Returns `Ok(None)` if the message has not arrived yet, if records preceding it are
+    /// yet to be received or if there is a pending [`receive`] call for it. In that case
+    /// nothing is registered for this record, so it is up to the caller to try again later
+    /// or to call [`receive`].
+    ///
+    /// ## Errors
+    /// Returns an error if receiving fails
+    ///
+    /// ## Panics
+    /// If a message for this record was already received.
+    ///
+    /// [`receive`]: Self::receive
+    pub fn try_receive(&self, record_id: RecordId) -> Result<Option<M>, Error<Role>> {
         self.unordered_rx
-            .recv::<M, _>(record_id)
-            .await
-            .map_err(|e| match e {
-                UnorderedReceiverError::DeserializeFailed(inner) => Error::DeserializeFailed {
-                    channel_id: self.channel_id.clone(),
-                    inner,
-                },
-                UnorderedReceiverError::EndOfStream(inner) => Error::EndOfStream {
-                    channel_id: self.channel_id.clone(),
-                    inner,
-                },
-            })
+            .try_recv::<M, _>(record_id)
+            .transpose()
+            .map_err(|e| self.map_err(e))
+    }
+
+    fn map_err(&self, e: UnorderedReceiverError) -> Error<Role> {
+        match e {
+            UnorderedReceiverError::DeserializeFailed(inner) => Error::DeserializeFailed {
+                channel_id: self.channel_id.clone(),
+                inner,
+            },
+            UnorderedReceiverError::EndOfStream(inner) => Error::EndOfStream {
+                channel_id: self.channel_id.clone(),
+                inner,
+            },
+        }
     }
 }
 
@@ -128,6 +216,43 @@ impl<I: TransportIdentity, S: Clone> GatewayReceivers<I, S> {
     }
 }
 
+impl GatewayReceivers<Role, UR> {
+    /// Returns every channel in this collection along with the number of records received
+    /// from it so far. Channels are ordered by their id.
+    pub fn received_records(&self) -> Vec<(ChannelId<Role>, usize)> {
+        let mut received = self
+            .inner
+            .iter()
+            .map(|entry| (entry.key().clone(), entry.value().received()))
+            .collect::<Vec<_>>();
+        received.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+
+        received
+    }
+
+    /// Returns channels in this collection that still expect records, along with the records
+    /// they are waiting for. See [`UnorderedReceiver::expected`]. Channels are ordered by their
+    /// id.
+    pub fn expected_records(&self) -> Vec<(ChannelId<Role>, Range<RecordId>)> {
+        let mut expected = self
+            .inner
+            .iter()
+            .filter_map(|entry| {
+                let records = entry.value().expected();
+                (!records.is_empty()).then(|| {
+                    (
+                        entry.key().clone(),
+                        RecordId::from(records.start)..RecordId::from(records.end),
+                    )
+                })
+            })
+            .collect::<Vec<_>>();
+        expected.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+
+        expected
+    }
+}
+
 impl Stream for ShardReceiveStream {
     type Item = <<ShardTransportImpl as Transport>::RecordsStream as Stream>::Item;
 
@@ -1,29 +1,39 @@
 use std::{
+    any::type_name,
     borrow::Borrow,
-    fmt::Debug,
+    fmt::{Debug, Display, Formatter},
     marker::PhantomData,
     num::NonZeroUsize,
+    ops::Range,
     pin::Pin,
     task::{Context, Poll},
+    time::{Duration, Instant},
 };
 
 use dashmap::{mapref::entry::Entry, DashMap};
-use futures::Stream;
-use ipa_metrics::counter;
+use futures::{ready, Stream};
+use ipa_metrics::{counter, LabelValue};
 #[cfg(all(test, feature = "shuttle"))]
 use shuttle::future as tokio;
 use typenum::Unsigned;
 
 use crate::{
     helpers::{
-        buffers::OrderingSender, routing::RouteId, ChannelId, Error, GatewayConfig, Message,
-        TotalRecords, Transport, TransportIdentity,
+        buffers::{OrderingSender, OrderingSenderError},
+        routing::RouteId,
+        ChannelId, Error, GatewayConfig, Message, TotalRecords, Transport, TransportIdentity,
     },
     protocol::{QueryId, RecordId},
-    sync::Arc,
+    sync::{
+        atomic::{
+            AtomicBool, AtomicUsize,
+            Ordering::{AcqRel, Acquire},
+        },
+        Arc, Mutex,
+    },
     telemetry::{
-        labels::{ROLE, STEP},
-        metrics::{BYTES_SENT, RECORDS_SENT},
+        labels::{FILL, FLUSH, ROLE, STEP},
+        metrics::{BYTES_SENT, RECORDS_SENT, SEND_BUFFER_FILL_RATIO, SEND_BUFFER_FLUSHES},
     },
     utils::non_zero_prev_power_of_two,
 };
@@ -45,10 +55,46 @@ pub(super) struct GatewaySender<I> {
     channel_id: ChannelId<I>,
     ordering_tx: OrderingSender,
     total_records: TotalRecords,
+    /// The number of records for channels created with [`TotalRecords::Indeterminate`],
+    /// once it becomes known. See [`GatewaySender::finalize_total_records`].
+    finalized_total_records: Mutex<Option<NonZeroUsize>>,
+    /// Set by whoever closes the channel after the last record, so it is closed only once.
+    end_closed: AtomicBool,
+    /// Number of records written into [`OrderingSender`]. Unlike its next index, this does not
+    /// count the operation that closes the channel.
+    records_written: AtomicUsize,
+    send_timeout: Option<Duration>,
+    /// See [`GatewayConfig::query_deadline`].
+    deadline: Option<Instant>,
+    /// The size of a single record, in bytes.
+    record_size: NonZeroUsize,
+    /// Size of one batch handed to the network layer, in bytes. Channels with indeterminate
+    /// number of records hand over every record individually until it is finalized.
+    batch_read_size: NonZeroUsize,
+    /// Name of the message type this channel was opened for. Only used to detect gates that
+    /// are reused for unrelated messages.
+    message_type: &'static str,
+}
+
+/// Reason why the send buffer made a batch available to the network layer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FlushKind {
+    /// Batch reached the configured read size.
+    Full,
+    /// Channel was closed before batch reached the configured read size.
+    Forced,
 }
 
 struct GatewaySendStream<I> {
     inner: Arc<GatewaySender<I>>,
+    /// Identity of this helper, used to label the flush metrics.
+    sender_id: I,
+    /// See [`GatewayConfig::sink_latency`].
+    #[cfg(not(feature = "shuttle"))]
+    sink_latency: Option<Duration>,
+    /// Batch taken from the send buffer that is held until the sink latency has passed.
+    #[cfg(not(feature = "shuttle"))]
+    delayed: Option<(Pin<Box<::tokio::time::Sleep>>, Vec<u8>)>,
 }
 
 /// Configuration for each [`GatewaySender`]. All values stored here
@@ -77,11 +123,30 @@ impl<I: TransportIdentity> Default for GatewaySenders<I> {
 }
 
 impl<I: TransportIdentity> GatewaySender<I> {
-    fn new(channel_id: ChannelId<I>, tx: OrderingSender, total_records: TotalRecords) -> Self {
+    fn new(
+        channel_id: ChannelId<I>,
+        config: &SendChannelConfig,
+        send_timeout: Option<Duration>,
+        deadline: Option<Instant>,
+        batch_read_size: NonZeroUsize,
+        message_type: &'static str,
+    ) -> Self {
         Self {
             channel_id,
-            ordering_tx: tx,
-            total_records,
+            ordering_tx: OrderingSender::new(
+                config.total_capacity,
+                config.record_size,
+                config.read_size,
+            ),
+            total_records: config.total_records,
+            finalized_total_records: Mutex::new(None),
+            end_closed: AtomicBool::new(false),
+            records_written: AtomicUsize::new(0),
+            send_timeout,
+            deadline,
+            record_size: config.record_size,
+            batch_read_size,
+            message_type,
         }
     }
 
@@ -90,48 +155,193 @@ impl<I: TransportIdentity> GatewaySender<I> {
         record_id: RecordId,
         msg: B,
     ) -> Result<(), Error<I>> {
-        debug_assert!(
-            self.total_records.is_specified(),
-            "total_records cannot be unspecified when sending"
-        );
-        if let TotalRecords::Specified(count) = self.total_records {
+        if !self.total_records.is_specified() {
+            return Err(Error::TotalRecordsUnspecified {
+                channel_id: self.channel_id.clone(),
+            });
+        }
+        let total_records = self.total_records();
+        if let TotalRecords::Specified(count) = total_records {
             if usize::from(record_id) >= count.get() {
                 return Err(Error::TooManyRecords {
                     record_id,
                     channel_id: self.channel_id.clone(),
-                    total_records: self.total_records,
+                    total_records,
                 });
             }
         }
 
-        // TODO: make OrderingSender::send fallible
-        // TODO: test channel close
         let i = usize::from(record_id);
-        self.ordering_tx.send(i, msg).await;
-        if self.total_records.is_last(record_id) {
-            self.ordering_tx.close(i + 1).await;
+        let sent = match self.deadline {
+            #[cfg(not(feature = "shuttle"))]
+            Some(deadline) => {
+                let expired = || Error::DeadlineExceeded {
+                    channel_id: self.channel_id.clone(),
+                    record_id,
+                };
+                // sends that don't need to wait for capacity must fail as well
+                if Instant::now() >= deadline {
+                    return Err(expired());
+                }
+                ::tokio::time::timeout_at(deadline.into(), self.send_with_timeout(record_id, msg))
+                    .await
+                    .map_err(|_| expired())??
+            }
+            _ => self.send_with_timeout(record_id, msg).await?,
+        };
+        sent.map_err(|_: OrderingSenderError| Error::ChannelClosed {
+            channel_id: self.channel_id.clone(),
+        })?;
+        self.records_written.fetch_add(1, AcqRel);
+        // The number of records may have been finalized while this record was being sent,
+        // so it needs to be checked again.
+        if self.total_records().is_last(record_id) {
+            self.close_after_last(i + 1).await;
         }
 
         Ok(())
     }
 
+    async fn send_with_timeout<M: Message, B: Borrow<M>>(
+        &self,
+        record_id: RecordId,
+        msg: B,
+    ) -> Result<Result<(), OrderingSenderError>, Error<I>> {
+        let i = usize::from(record_id);
+        Ok(match self.send_timeout {
+            #[cfg(not(feature = "shuttle"))]
+            Some(timeout) => {
+                let start = Instant::now();
+                ::tokio::time::timeout(timeout, self.ordering_tx.send(i, msg))
+                    .await
+                    .map_err(|_| {
+                        // Records after this one would wait for it forever.
+                        self.ordering_tx.abort();
+                        Error::SendTimeout {
+                            channel_id: self.channel_id.clone(),
+                            record_id,
+                            elapsed: start.elapsed(),
+                        }
+                    })?
+            }
+            _ => self.ordering_tx.send(i, msg).await,
+        })
+    }
+
+    /// Sets the number of records for a channel that was created with
+    /// [`TotalRecords::Indeterminate`]. From this point on, records are handed over to the network
+    /// layer in batches, as they are for channels with a known number of records, and the channel
+    /// is closed after record `count - 1` is sent. If all of them have been sent already, it is
+    /// closed right away.
+    ///
+    /// ## Panics
+    /// If this channel was not created with [`TotalRecords::Indeterminate`], if the number of
+    /// records has been finalized already, if `count` is zero or if more than `count` records
+    /// have been sent already.
+    pub async fn finalize_total_records(&self, count: usize) {
+        assert!(
+            self.total_records.is_indeterminate(),
+            "{:?}: only channels with indeterminate number of records can be finalized, this one has {}",
+            self.channel_id,
+            self.total_records
+        );
+        let count = NonZeroUsize::new(count).expect("total number of records must not be zero");
+        let sent = {
+            let mut finalized = self.finalized_total_records.lock().unwrap();
+            assert!(
+                finalized.is_none(),
+                "{:?}: total number of records is already finalized",
+                self.channel_id
+            );
+            *finalized = Some(count);
+            // Must be read while holding the lock, otherwise the last record could be sent
+            // without anyone noticing it was the last one.
+            self.ordering_tx.next_index()
+        };
+        assert!(
+            sent <= count.get(),
+            "{:?}: {sent} records have been sent already, can't finalize it at {count}",
+            self.channel_id
+        );
+        self.ordering_tx.set_read_threshold(self.batch_read_size);
+
+        if sent == count.get() {
+            self.close_after_last(sent).await;
+        }
+    }
+
+    /// Returns the total number of records for this channel, taking finalization of
+    /// indeterminate channels into account.
+    pub fn total_records(&self) -> TotalRecords {
+        match self.total_records {
+            TotalRecords::Indeterminate => self
+                .finalized_total_records
+                .lock()
+                .unwrap()
+                .map_or(TotalRecords::Indeterminate, TotalRecords::Specified),
+            total_records => total_records,
+        }
+    }
+
+    /// Returns the number of records sent through this channel so far.
+    fn sent_records(&self) -> usize {
+        self.records_written.load(Acquire)
+    }
+
+    async fn close_after_last(&self, at: usize) {
+        if !self.end_closed.swap(true, AcqRel) {
+            self.ordering_tx.close(at).await;
+        }
+    }
+
     #[cfg(feature = "stall-detection")]
     pub fn waiting(&self) -> std::collections::BTreeSet<usize> {
         self.ordering_tx.waiting()
     }
 
+    pub fn is_closed(&self) -> bool {
+        self.ordering_tx.is_closed()
+    }
+
+    /// Returns the number of records buffered in this channel that haven't been handed
+    /// over to the network layer yet.
     #[cfg(feature = "stall-detection")]
-    pub fn total_records(&self) -> TotalRecords {
-        self.total_records
+    pub fn pending_records(&self) -> usize {
+        self.ordering_tx.buffered() / self.record_size
     }
 
-    pub fn is_closed(&self) -> bool {
-        self.ordering_tx.is_closed()
+    /// Resolves once this channel is closed and all data sent through it has been handed over
+    /// to the network layer. It also resolves if the receiving end of the send buffer is
+    /// gone, because nothing else is going to be handed over after that.
+    pub async fn wait_closed(&self) {
+        self.ordering_tx.drained().await;
+    }
+
+    /// Returns the largest number of bytes this channel has buffered so far.
+    #[cfg(feature = "stall-detection")]
+    pub fn high_water_mark(&self) -> usize {
+        self.ordering_tx.high_water_mark()
     }
 
     pub async fn close(&self, at: RecordId) {
         self.ordering_tx.close(at.into()).await;
     }
+
+    /// Closes this sender right after the last record written into it, so any data that is
+    /// still buffered becomes available to the transport. Sends that are still in flight and
+    /// any later sends fail with [`Error::ChannelClosed`]. Returns the range of records that
+    /// will never be sent, if the channel expected more.
+    pub fn shutdown(&self) -> Option<Range<RecordId>> {
+        // Claim the close, so a send of the last record racing with this doesn't close it again.
+        self.end_closed.swap(true, AcqRel);
+        let written = self.ordering_tx.abort();
+        match self.total_records() {
+            TotalRecords::Specified(count) if written < count.get() => {
+                Some(RecordId::from(written)..RecordId::from(count.get()))
+            }
+            _ => None,
+        }
+    }
 }
 
 impl<I: TransportIdentity, M: Message> SendingEnd<I, M> {
@@ -183,6 +393,24 @@ impl<I: TransportIdentity, M: Message> SendingEnd<I, M> {
             self.inner.close(at).await;
         }
     }
+
+    /// Sets the total number of records for a channel that was created with
+    /// [`TotalRecords::Indeterminate`], once it becomes known. This allows it to send records
+    /// in larger batches and to be closed after the last record.
+    ///
+    /// ## Panics
+    /// If this channel does not have an indeterminate number of records, if it is finalized
+    /// twice, if `count` is zero or if more than `count` records have been sent through it already.
+    pub async fn finalize_total_records(&self, count: usize) {
+        self.inner.finalize_total_records(count).await;
+    }
+
+    /// Resolves once this channel is closed and all data sent through it has been handed over
+    /// to the network layer. It also resolves if the network layer stopped taking data from
+    /// this channel, because nothing else is going to be handed over after that.
+    pub async fn wait_closed(&self) {
+        self.inner.wait_closed().await;
+    }
 }
 
 impl<I: TransportIdentity> GatewaySenders<I> {
@@ -194,28 +422,59 @@ impl<I: TransportIdentity> GatewaySenders<I> {
         transport: &T,
         config: GatewayConfig,
         query_id: QueryId,
-        total_records: TotalRecords, // TODO track children for indeterminate senders
+        total_records: TotalRecords,
+        deadline: Option<Instant>,
     ) -> Arc<GatewaySender<I>> {
-        assert!(
-            total_records.is_specified(),
-            "unspecified total records for {channel_id:?}"
-        );
+        if !total_records.is_specified() {
+            // Nothing can be sent through this channel, so it is not registered with the
+            // transport and every send fails.
+            let config = SendChannelConfig::new::<M>(config, total_records);
+            return Self::new_sender(
+                &config,
+                channel_id.clone(),
+                None,
+                None,
+                config.read_size,
+                type_name::<M>(),
+            );
+        }
 
         // TODO: raw entry API would be nice to have here but it's not exposed yet
         match self.inner.entry(channel_id.clone()) {
-            Entry::Occupied(entry) => Arc::clone(entry.get()),
+            Entry::Occupied(entry) => {
+                debug_assert_eq!(
+                    entry.get().message_type,
+                    type_name::<M>(),
+                    "{channel_id:?} is already used to send a different message type"
+                );
+                Arc::clone(entry.get())
+            }
             Entry::Vacant(entry) => {
+                let send_timeout = config.send_timeout;
+                let sink_latency = config.sink_latency;
+                // Indeterminate channels switch to this read size once they are finalized
+                let batch_read_size =
+                    SendChannelConfig::new::<M>(config, TotalRecords::ONE).read_size;
                 let config = SendChannelConfig::new::<M>(config, total_records);
                 tracing::trace!("send configuration for {channel_id:?}: {config:?}");
-                let sender = Self::new_sender(&config, channel_id.clone());
+                let sender = Self::new_sender(
+                    &config,
+                    channel_id.clone(),
+                    send_timeout,
+                    deadline,
+                    batch_read_size,
+                    type_name::<M>(),
+                );
                 entry.insert(Arc::clone(&sender));
 
                 tokio::spawn({
                     let ChannelId { peer, gate } = channel_id.clone();
                     let transport = transport.clone();
-                    let stream = GatewaySendStream {
-                        inner: Arc::clone(&sender),
-                    };
+                    let stream = GatewaySendStream::new(
+                        Arc::clone(&sender),
+                        transport.identity(),
+                        sink_latency,
+                    );
                     async move {
                         // TODO(651): In the HTTP case we probably need more robust error handling here.
                         transport
@@ -230,21 +489,201 @@ impl<I: TransportIdentity> GatewaySenders<I> {
         }
     }
 
-    fn new_sender(config: &SendChannelConfig, channel_id: ChannelId<I>) -> Arc<GatewaySender<I>> {
+    /// Returns every channel that has records buffered, along with the number of
+    /// those records. Channels are ordered by their id.
+    #[cfg(feature = "stall-detection")]
+    pub fn pending_channels(&self) -> Vec<(ChannelId<I>, usize)> {
+        let mut pending = self
+            .inner
+            .iter()
+            .map(|entry| (entry.key().clone(), entry.value().pending_records()))
+            .filter(|(_, records)| *records > 0)
+            .collect::<Vec<_>>();
+        pending.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+
+        pending
+    }
+
+    /// Returns every channel in this collection along with the number of records sent
+    /// through it so far. Channels are ordered by their id.
+    pub fn sent_records(&self) -> Vec<(ChannelId<I>, usize)> {
+        let mut sent = self
+            .inner
+            .iter()
+            .map(|entry| (entry.key().clone(), entry.value().sent_records()))
+            .collect::<Vec<_>>();
+        sent.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+
+        sent
+    }
+
+    /// Shuts down every sender in this collection. See [`GatewaySender::shutdown`].
+    /// Returns records that were left unsent for each channel, ordered by channel id.
+    pub fn shutdown(&self) -> Vec<(ChannelId<I>, Range<RecordId>)> {
+        let mut unsent = self
+            .inner
+            .iter()
+            .filter_map(|entry| {
+                entry
+                    .value()
+                    .shutdown()
+                    .map(|records| (entry.key().clone(), records))
+            })
+            .collect::<Vec<_>>();
+        unsent.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+
+        unsent
+    }
+
+    fn new_sender(
+        config: &SendChannelConfig,
+        channel_id: ChannelId<I>,
+        send_timeout: Option<Duration>,
+        deadline: Option<Instant>,
+        batch_read_size: NonZeroUsize,
+        message_type: &'static str,
+    ) -> Arc<GatewaySender<I>> {
         Arc::new(GatewaySender::new(
             channel_id,
-            OrderingSender::new(config.total_capacity, config.record_size, config.read_size),
-            config.total_records,
+            config,
+            send_timeout,
+            deadline,
+            batch_read_size,
+            message_type,
         ))
     }
 }
 
-impl<I: Debug> Stream for GatewaySendStream<I> {
+impl<I: TransportIdentity> GatewaySendStream<I> {
+    #[cfg_attr(feature = "shuttle", allow(unused_variables))]
+    fn new(inner: Arc<GatewaySender<I>>, sender_id: I, sink_latency: Option<Duration>) -> Self {
+        Self {
+            inner,
+            sender_id,
+            #[cfg(not(feature = "shuttle"))]
+            sink_latency,
+            #[cfg(not(feature = "shuttle"))]
+            delayed: None,
+        }
+    }
+
+    /// Takes the next batch from the send buffer and, if sink latency is configured, holds it
+    /// for that long before handing it over to the network layer. Shuttle has no notion of
+    /// time, so there is no latency injected there.
+    fn poll_delayed(&mut self, cx: &mut Context<'_>) -> Poll<Option<Vec<u8>>> {
+        #[cfg(not(feature = "shuttle"))]
+        if let Some(latency) = self.sink_latency {
+            if self.delayed.is_none() {
+                let Some(batch) = ready!(self.take_next(cx)) else {
+                    return Poll::Ready(None);
+                };
+                self.delayed = Some((Box::pin(::tokio::time::sleep(latency)), batch));
+            }
+            let (delay, _) = self.delayed.as_mut().unwrap();
+            ready!(std::future::Future::poll(delay.as_mut(), cx));
+
+            return Poll::Ready(self.delayed.take().map(|(_, batch)| batch));
+        }
+
+        self.take_next(cx)
+    }
+
+    /// Takes the next batch from the send buffer and records how full it was.
+    fn take_next(&self, cx: &Context<'_>) -> Poll<Option<Vec<u8>>> {
+        let Some((batch, read_size)) = ready!(self.inner.ordering_tx.take_next_sized(cx)) else {
+            return Poll::Ready(None);
+        };
+        self.record_flush(batch.len(), read_size);
+
+        Poll::Ready(Some(batch))
+    }
+
+    /// Batches shorter than the read size are only taken once the channel is closed.
+    fn record_flush(&self, len: usize, read_size: usize) {
+        let kind = if len < read_size {
+            FlushKind::Forced
+        } else {
+            FlushKind::Full
+        };
+        let fill = u32::try_from((len * 10).div_ceil(read_size) * 10)
+            .expect("batch can't be larger than the read size");
+        counter!(SEND_BUFFER_FLUSHES, 1,
+            STEP => &self.inner.channel_id.gate,
+            ROLE => &self.sender_id,
+            FLUSH => &kind
+        );
+        counter!(SEND_BUFFER_FILL_RATIO, 1,
+            STEP => &self.inner.channel_id.gate,
+            ROLE => &self.sender_id,
+            FILL => &fill
+        );
+    }
+}
+
+impl<I> GatewaySendStream<I> {
+    /// Number of bytes taken from the send buffer that are held back by the sink latency.
+    #[cfg(all(feature = "stall-detection", not(feature = "shuttle")))]
+    fn delayed_bytes(&self) -> usize {
+        self.delayed.as_ref().map_or(0, |(_, batch)| batch.len())
+    }
+
+    /// Returns the number of bytes written into this channel that were never taken by the
+    /// network layer, along with the records still waiting to be written. Returns `None` if
+    /// everything sent so far has been delivered.
+    #[cfg(all(feature = "stall-detection", not(feature = "shuttle")))]
+    fn undelivered(&self) -> Option<(usize, std::collections::BTreeSet<usize>)> {
+        let bytes = self.inner.ordering_tx.buffered() + self.delayed_bytes();
+        let waiting = self.inner.ordering_tx.waiting();
+        (bytes > 0 || !waiting.is_empty()).then_some((bytes, waiting))
+    }
+}
+
+// Nothing inside this stream is structurally pinned, the sleep future is boxed.
+impl<I> Unpin for GatewaySendStream<I> {}
+
+impl<I: TransportIdentity> Stream for GatewaySendStream<I> {
     type Item = Vec<u8>;
 
     #[tracing::instrument(level = "trace", name = "send_stream", skip_all, fields(to = ?self.inner.channel_id.peer, gate = ?self.inner.channel_id.gate))]
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        Pin::get_mut(self).inner.ordering_tx.take_next(cx)
+        Pin::get_mut(self).poll_delayed(cx)
+    }
+}
+
+impl<I> Drop for GatewaySendStream<I> {
+    fn drop(&mut self) {
+        // Shuttle drops tasks that are still running when a test ends outside of its scheduler,
+        // where the send buffer lock can't be taken. Streams owned by those tasks are neither
+        // inspected nor closed there.
+        #[cfg(all(feature = "stall-detection", not(feature = "shuttle")))]
+        if let Some((bytes, waiting)) = self.undelivered() {
+            tracing::warn!(
+                "send stream for {:?} dropped with {bytes} bytes buffered and records {waiting:?} waiting to be sent",
+                self.inner.channel_id.gate,
+            );
+        }
+        // Whatever is sent through this channel from now on, will never reach the peer.
+        #[cfg(not(feature = "shuttle"))]
+        self.inner.ordering_tx.close_receiver();
+    }
+}
+
+impl Display for FlushKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Full => "full",
+            Self::Forced => "forced",
+        })
+    }
+}
+
+impl LabelValue for FlushKind {
+    fn hash(&self) -> u64 {
+        u64::from(*self as u32)
+    }
+
+    fn boxed(&self) -> Box<dyn LabelValue> {
+        Box::new(*self)
     }
 }
 
@@ -297,8 +736,11 @@ impl SendChannelConfig {
 
 #[cfg(all(test, unit_test))]
 mod test {
-    use std::num::NonZeroUsize;
+    #[cfg(feature = "stall-detection")]
+    use std::collections::BTreeSet;
+    use std::{any::type_name, num::NonZeroUsize, pin::pin};
 
+    use futures::{future::poll_immediate, StreamExt};
     use proptest::proptest;
     use typenum::Unsigned;
 
@@ -307,8 +749,14 @@ mod test {
             boolean_array::{BA16, BA20, BA256, BA3, BA32, BA7},
             Serializable,
         },
-        helpers::{gateway::send::SendChannelConfig, GatewayConfig, TotalRecords},
+        helpers::{
+            gateway::send::{GatewaySendStream, GatewaySenders, SendChannelConfig},
+            ChannelId, Error, GatewayConfig, Role, TotalRecords,
+        },
+        protocol::{Gate, RecordId},
         secret_sharing::SharedValue,
+        sync::Arc,
+        test_executor::run,
     };
 
     impl Default for SendChannelConfig {
@@ -469,6 +917,125 @@ mod test {
         assert_eq!(0, config.total_capacity.get() % config.read_size.get());
     }
 
+    #[test]
+    fn send_after_stream_dropped() {
+        run(|| async {
+            let config = send_config::<BA32, 4, 4>(TotalRecords::specified(4).unwrap());
+            let sender = GatewaySenders::<Role>::new_sender(
+                &config,
+                ChannelId::new(Role::H2, Gate::default()),
+                None,
+                None,
+                config.read_size,
+                type_name::<BA32>(),
+            );
+            sender.send(RecordId::FIRST, BA32::ZERO).await.unwrap();
+
+            // nobody is going to read from this channel anymore
+            drop(GatewaySendStream::new(Arc::clone(&sender), Role::H1, None));
+
+            let err = sender
+                .send(RecordId::from(1_u32), BA32::ZERO)
+                .await
+                .unwrap_err();
+            assert!(matches!(err, Error::ChannelClosed { .. }), "{err:?}");
+        });
+    }
+
+    #[test]
+    fn wait_closed() {
+        run(|| async {
+            let config = send_config::<BA32, 4, 4>(TotalRecords::specified(2).unwrap());
+            let sender = GatewaySenders::<Role>::new_sender(
+                &config,
+                ChannelId::new(Role::H2, Gate::default()),
+                None,
+                None,
+                config.read_size,
+                type_name::<BA32>(),
+            );
+            let mut stream = GatewaySendStream::new(Arc::clone(&sender), Role::H1, None);
+            let mut closed = pin!(sender.wait_closed());
+            assert_eq!(None, poll_immediate(&mut closed).await);
+
+            for i in 0..2_u32 {
+                sender.send(RecordId::from(i), BA32::ZERO).await.unwrap();
+            }
+            // the channel is closed now, but both records are still buffered
+            assert!(sender.is_closed());
+            for _ in 0..2 {
+                assert_eq!(None, poll_immediate(&mut closed).await);
+                assert_eq!(
+                    Some(<BA32 as Serializable>::Size::USIZE),
+                    stream.next().await.map(|batch| batch.len())
+                );
+            }
+            assert_eq!(Some(()), poll_immediate(&mut closed).await);
+            assert_eq!(None, stream.next().await);
+        });
+    }
+
+    #[test]
+    #[cfg(all(feature = "stall-detection", not(feature = "shuttle")))]
+    fn undelivered_on_drop() {
+        run(|| async {
+            let config = send_config::<BA32, 4, 4>(TotalRecords::specified(4).unwrap());
+            let sender = GatewaySenders::<Role>::new_sender(
+                &config,
+                ChannelId::new(Role::H2, Gate::default()),
+                None,
+                None,
+                config.read_size,
+                type_name::<BA32>(),
+            );
+            let stream = GatewaySendStream::new(Arc::clone(&sender), Role::H1, None);
+            assert_eq!(None, stream.undelivered());
+
+            sender.send(RecordId::FIRST, BA32::ZERO).await.unwrap();
+            assert_eq!(
+                Some((<BA32 as Serializable>::Size::USIZE, BTreeSet::new())),
+                stream.undelivered()
+            );
+
+            drop(stream);
+        });
+    }
+
+    #[test]
+    #[cfg(feature = "stall-detection")]
+    fn pending_channels() {
+        run(|| async {
+            let config = send_config::<BA32, 4, 16>(TotalRecords::specified(8).unwrap());
+            let senders = GatewaySenders::<Role>::default();
+            let [h2, h3] = [Role::H2, Role::H3].map(|role| {
+                let channel_id = ChannelId::new(role, Gate::default());
+                let sender = GatewaySenders::<Role>::new_sender(
+                    &config,
+                    channel_id.clone(),
+                    None,
+                    None,
+                    config.read_size,
+                    type_name::<BA32>(),
+                );
+                senders.inner.insert(channel_id, Arc::clone(&sender));
+                sender
+            });
+            assert!(senders.pending_channels().is_empty());
+
+            h3.send(RecordId::FIRST, BA32::ZERO).await.unwrap();
+            h2.send(RecordId::FIRST, BA32::ZERO).await.unwrap();
+            h2.send(RecordId::from(1_u32), BA32::ZERO).await.unwrap();
+
+            assert_eq!(
+                vec![
+                    (ChannelId::new(Role::H2, Gate::default()), 2),
+                    (ChannelId::new(Role::H3, Gate::default()), 1),
+                ],
+                senders.pending_channels()
+            );
+        });
+    }
+
     proptest! {
         #[test]
         fn config_prop(
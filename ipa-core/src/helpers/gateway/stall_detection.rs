@@ -73,9 +73,10 @@ mod gateway {
     use super::{receive, send, AtomicUsize, Debug, Formatter, ObserveState, Observed, Weak};
     use crate::{
         helpers::{
-            gateway::{Gateway, ShardTransportImpl, State},
+            gateway::{ChannelSummary, Gateway, ShardTransportImpl, ShutdownError, State},
             GatewayConfig, HelperChannelId, Message, MpcMessage, MpcReceivingEnd, MpcTransportImpl,
-            Role, RoleAssignment, SendingEnd, ShardChannelId, ShardReceivingEnd, TotalRecords,
+            PeerStatus, Role, RoleAssignment, SendingEnd, ShardChannelId, ShardReceivingEnd,
+            TotalRecords,
         },
         protocol::QueryId,
         sharding::{ShardConfiguration, ShardIndex},
@@ -99,6 +100,12 @@ mod gateway {
 
                 #[inline]
                 pub fn config(&self) -> &GatewayConfig;
+
+                #[inline]
+                pub fn peer_status(&self, peer: Role) -> PeerStatus;
+
+                #[inline]
+                pub fn active_channels(&self) -> Vec<ChannelSummary>;
             }
         }
 
@@ -124,8 +131,11 @@ mod gateway {
             {
                 use tracing::Instrument;
 
+                use crate::helpers::Transport;
+
                 tokio::spawn({
                     let gateway = r.to_observed();
+                    let mpc_transport = r.inner().gateway.transports.mpc.clone();
                     async move {
                         let mut last_sn_seen = 0;
                         loop {
@@ -134,7 +144,11 @@ mod gateway {
                             if let Some(now) = now {
                                 if now == last_sn_seen {
                                     if let Some(state) = gateway.get_state() {
-                                        tracing::warn!(sn = now, state = ?state, "Helper is stalled");
+                                        let peers = mpc_transport
+                                            .peers()
+                                            .map(|peer| (peer, mpc_transport.peer_status(peer)))
+                                            .collect::<Vec<_>>();
+                                        tracing::warn!(sn = now, state = ?state, peers = ?peers, "Helper is stalled");
                                     }
                                 }
                                 last_sn_seen = now;
@@ -198,6 +212,15 @@ mod gateway {
             )
         }
 
+        /// See [`Gateway::shutdown`].
+        ///
+        /// ## Errors
+        /// If some channels were closed before all of their records were sent, or if some
+        /// receives are still waiting for records.
+        pub fn shutdown(&self) -> Result<(), ShutdownError> {
+            self.inner.gateway.shutdown()
+        }
+
         pub fn to_observed(&self) -> Observed<Weak<State>> {
             // todo: inner.inner
             Observed::wrap(
@@ -276,6 +299,7 @@ mod receive {
     use std::{
         collections::BTreeMap,
         fmt::{Debug, Formatter},
+        ops::Range,
         pin::Pin,
         task::{Context, Poll},
     };
@@ -301,6 +325,10 @@ mod receive {
             to { self.advance(); self.inner() } {
                 #[inline]
                 pub async fn receive(&self, record_id: RecordId) -> Result<M, Error<Role>>;
+                #[inline]
+                pub fn try_receive(&self, record_id: RecordId) -> Result<Option<M>, Error<Role>>;
+                #[inline]
+                pub async fn receive_many(&self, records: Range<RecordId>) -> Result<Vec<M>, Error<Role>>;
             }
         }
     }
@@ -388,18 +416,22 @@ mod send {
                 pub async fn send<B: Borrow<M>>(&self, record_id: RecordId, msg: B) -> Result<(), Error<I>>;
                 #[inline]
                 pub async fn close(&self, at: RecordId);
+                #[inline]
+                pub async fn finalize_total_records(&self, count: usize);
+                #[inline]
+                pub async fn wait_closed(&self);
             }
         }
     }
 
-    pub struct WaitingTasks<I>(BTreeMap<ChannelId<I>, (TotalRecords, Vec<String>)>);
+    pub struct WaitingTasks<I>(BTreeMap<ChannelId<I>, (TotalRecords, usize, usize, Vec<String>)>);
 
     impl<I: TransportIdentity> Debug for WaitingTasks<I> {
         fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-            for (channel, (total, records)) in &self.0 {
+            for (channel, (total, pending, peak, records)) in &self.0 {
                 write!(
                     f,
-                    "\n\"{:?}\", to={:?}. Waiting to send records {:?} out of {total:?}.",
+                    "\n\"{:?}\", to={:?}. Waiting to send records {:?} out of {total:?}, {pending} records buffered, at most {peak} bytes.",
                     channel.gate, channel.peer, records
                 )?;
             }
@@ -412,12 +444,25 @@ mod send {
         type State = WaitingTasks<I>;
 
         fn get_state(&self) -> Option<Self::State> {
+            let pending = self
+                .pending_channels()
+                .into_iter()
+                .collect::<BTreeMap<_, _>>();
             let mut state = BTreeMap::new();
             for entry in &self.inner {
                 let channel = entry.key();
                 let sender = entry.value();
                 if let Some(sender_state) = sender.get_state() {
-                    state.insert(channel.clone(), (sender.total_records(), sender_state));
+                    let buffered = pending.get(channel).copied().unwrap_or_default();
+                    state.insert(
+                        channel.clone(),
+                        (
+                            sender.total_records(),
+                            buffered,
+                            sender.high_water_mark(),
+                            sender_state,
+                        ),
+                    );
                 }
             }
 
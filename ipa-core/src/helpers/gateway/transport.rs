@@ -3,8 +3,8 @@ use futures::Stream;
 
 use crate::{
     helpers::{
-        transport::routing::RouteId, MpcTransportImpl, NoResourceIdentifier, QueryIdBinding, Role,
-        RoleAssignment, RouteParams, StepBinding, Transport,
+        transport::routing::RouteId, MpcTransportImpl, NoResourceIdentifier, PeerStatus,
+        QueryIdBinding, Role, RoleAssignment, RouteParams, StepBinding, Transport,
     },
     protocol::{Gate, QueryId},
     sharding::ShardIndex,
@@ -50,6 +50,10 @@ impl Transport for RoleResolvingTransport {
         self.inner.peer_count()
     }
 
+    fn peer_status(&self, peer: Role) -> PeerStatus {
+        self.inner.peer_status(self.roles.identity(peer))
+    }
+
     async fn send<
         D: Stream<Item = Vec<u8>> + Send + 'static,
         Q: QueryIdBinding,
@@ -110,8 +110,16 @@ where
     hash
 }
 
+/// Domain tag used by [`hash_to_field`] when deriving the Fiat-Shamir challenge for the
+/// distributed zero-knowledge proofs.
+pub const ZKP_CHALLENGE_DOMAIN: &[u8] = b"zkp-challenge";
+
 /// This function takes two hashes, combines them together and returns a single field element.
 ///
+/// The `domain` tag is absorbed before the two hashes, so protocols that use distinct tags
+/// never derive the same challenge from the same inputs. Because both hashes have a fixed
+/// length, the tag does not need to be length-prefixed.
+///
 /// Its use is tailored to malicious security requirements where the random challenge point `r`
 /// must be uniformly drawn from the field `F`, with the constraint that it does NOT appear
 /// in the set {0, 1, ..., L-1}.
@@ -125,7 +133,7 @@ where
 /// # Panics
 /// If field size is too large compared to 128 bits of entropy required to generate `r` or
 /// if exclude range is greater than half size of the field `F`.
-pub fn hash_to_field<F>(left: &Hash, right: &Hash, exclude_to: u128) -> F
+pub fn hash_to_field<F>(domain: &[u8], left: &Hash, right: &Hash, exclude_to: u128) -> F
 where
     F: PrimeField + FromRandomU128,
 {
@@ -143,8 +151,14 @@ where
     );
 
     // set state
-    let combine = compute_hash([left, right]);
+    let mut sha = Sha256::new();
+    sha.update(domain);
     let mut buf = GenericArray::default();
+    for hash in [left, right] {
+        Serializable::serialize(hash, &mut buf);
+        sha.update(buf);
+    }
+    let combine = Hash(sha.finalize());
     combine.serialize(&mut buf);
 
     // compute hash as a field element
@@ -168,7 +182,7 @@ mod test {
     use super::{compute_hash, compute_possibly_empty_hash, Hash};
     use crate::{
         ff::{Fp31, Fp32BitPrime, Serializable},
-        helpers::hashing::hash_to_field,
+        helpers::hashing::{hash_to_field, ZKP_CHALLENGE_DOMAIN},
     };
 
     #[test]
@@ -246,7 +260,12 @@ mod test {
             left.push(rng.gen::<Fp32BitPrime>());
             right.push(rng.gen::<Fp32BitPrime>());
         }
-        let r1: Fp32BitPrime = hash_to_field(&compute_hash(&left), &compute_hash(&right), EXCLUDE);
+        let r1: Fp32BitPrime = hash_to_field(
+            ZKP_CHALLENGE_DOMAIN,
+            &compute_hash(&left),
+            &compute_hash(&right),
+            EXCLUDE,
+        );
 
         // modify one, randomly selected element in the list
         let random_index = rng.gen::<usize>() % LIST_LENGTH;
@@ -258,7 +277,12 @@ mod test {
             right[random_index] = modified_value;
         }
 
-        let r2: Fp32BitPrime = hash_to_field(&compute_hash(&left), &compute_hash(&right), EXCLUDE);
+        let r2: Fp32BitPrime = hash_to_field(
+            ZKP_CHALLENGE_DOMAIN,
+            &compute_hash(&left),
+            &compute_hash(&right),
+            EXCLUDE,
+        );
 
         assert_ne!(
             r1, r2,
@@ -266,6 +290,26 @@ mod test {
         );
     }
 
+    #[test]
+    fn domain_changes_field_element() {
+        const EXCLUDE: u128 = 7;
+
+        let mut rng = thread_rng();
+        let left = compute_hash((0..5).map(|_| rng.gen::<Fp32BitPrime>()));
+        let right = compute_hash((0..5).map(|_| rng.gen::<Fp32BitPrime>()));
+
+        let r1: Fp32BitPrime = hash_to_field(ZKP_CHALLENGE_DOMAIN, &left, &right, EXCLUDE);
+        let r2: Fp32BitPrime = hash_to_field(b"other-protocol", &left, &right, EXCLUDE);
+        let r3: Fp32BitPrime = hash_to_field(ZKP_CHALLENGE_DOMAIN, &left, &right, EXCLUDE);
+
+        // There is a 1 in 2^32 chance that the two domains produce the same value.
+        assert_ne!(
+            r1, r2,
+            "different domains must produce different challenges"
+        );
+        assert_eq!(r1, r3);
+    }
+
     #[test]
     fn check_hash_from_owned_values() {
         let mut rng = thread_rng();
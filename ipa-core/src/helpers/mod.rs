@@ -26,7 +26,7 @@ use std::ops::{Index, IndexMut};
 
 /// to validate that transport can actually send streams of this type
 #[cfg(test)]
-pub use buffers::OrderingSender;
+pub use buffers::{OrderingSender, OrderingSenderError};
 pub use error::Error;
 pub use futures::MaybeFuture;
 use serde::{Deserialize, Serialize, Serializer};
@@ -57,7 +57,7 @@ mod gateway_exports {
 }
 
 pub use cross_shard_prss::gen_and_distribute as setup_cross_shard_prss;
-pub use gateway::GatewayConfig;
+pub use gateway::{ChannelKind, ChannelSummary, GatewayConfig, ShutdownError};
 // TODO: this type should only be available within infra. Right now several infra modules
 // are exposed at the root level. That makes it impossible to have a proper hierarchy here.
 pub use gateway::{
@@ -77,7 +77,7 @@ pub use transport::{
 pub use transport::{
     make_owned_handler, query, routing, ApiError, BodyStream, BroadcastError, BytesStream,
     HandlerBox, HandlerRef, HelperResponse, Identity as TransportIdentity, LengthDelimitedStream,
-    LogErrors, NoQueryId, NoResourceIdentifier, NoStep, QueryIdBinding, ReceiveRecords,
+    LogErrors, NoQueryId, NoResourceIdentifier, NoStep, PeerStatus, QueryIdBinding, ReceiveRecords,
     RecordsStream, RequestHandler, RouteParams, SingleRecordStream, StepBinding, StreamCollection,
     StreamKey, Transport, WrappedBoxBodyStream,
 };
@@ -7,8 +7,9 @@ use serde_json::json;
 use crate::{
     error::BoxError,
     helpers::{
-        query::PrepareQuery, transport::routing::Addr, BodyStream, HelperIdentity,
-        TransportIdentity,
+        query::PrepareQuery,
+        transport::routing::{Addr, RouteId},
+        BodyStream, HelperIdentity, TransportIdentity,
     },
     query::{
         NewQueryError, PrepareQueryError, ProtocolResult, QueryCompletionError, QueryInputError,
@@ -174,6 +175,8 @@ pub enum Error {
     DeserializationFailure(#[from] serde_json::Error),
     #[error("MalformedRequest: {0}")]
     BadRequest(BoxError),
+    #[error("No handler is registered to process {0:?} requests")]
+    NoHandler(RouteId),
 }
 
 /// Trait for custom-handling different request types made against MPC helper parties.
@@ -24,12 +24,12 @@ use crate::{
         in_memory_config::{self, DynStreamInterceptor},
         transport::routing::{Addr, RouteId},
         ApiError, BodyStream, HandlerRef, HelperIdentity, HelperResponse, NoResourceIdentifier,
-        QueryIdBinding, ReceiveRecords, RequestHandler, RouteParams, StepBinding, StreamCollection,
-        Transport, TransportIdentity,
+        PeerStatus, QueryIdBinding, ReceiveRecords, RequestHandler, RouteParams, StepBinding,
+        StreamCollection, Transport, TransportIdentity,
     },
     protocol::{Gate, QueryId},
     sharding::ShardIndex,
-    sync::{Arc, Weak},
+    sync::{Arc, Mutex, Weak},
 };
 
 type Packet<I> = (
@@ -66,7 +66,7 @@ pub enum Error<I> {
 /// incoming messages.
 pub struct InMemoryTransport<I> {
     identity: I,
-    connections: HashMap<I, ConnectionTx<I>>,
+    connections: Mutex<HashMap<I, ConnectionTx<I>>>,
     record_streams: StreamCollection<I, InMemoryStream>,
     config: TransportConfig,
 }
@@ -80,7 +80,7 @@ impl<I: TransportIdentity> InMemoryTransport<I> {
     ) -> Self {
         Self {
             identity,
-            connections,
+            connections: Mutex::new(connections),
             record_streams: StreamCollection::default(),
             config,
         }
@@ -105,11 +105,17 @@ impl<I: TransportIdentity> InMemoryTransport<I> {
 
                         let result = match addr.route {
                             RouteId::Records => {
-                                let query_id = addr.query_id.unwrap();
-                                let gate = addr.gate.unwrap();
-                                let from = addr.origin.unwrap();
-                                streams.add_stream((query_id, from, gate), stream);
-                                Ok(HelperResponse::ok())
+                                if let (Some(query_id), Some(gate), Some(from)) =
+                                    (addr.query_id, addr.gate, addr.origin)
+                                {
+                                    streams.add_stream((query_id, from, gate), stream);
+                                    Ok(HelperResponse::ok())
+                                } else {
+                                    Err(ApiError::BadRequest(
+                                        "records request must specify query id, gate and origin"
+                                            .into(),
+                                    ))
+                                }
                             }
                             RouteId::ReceiveQuery
                             | RouteId::PrepareQuery
@@ -118,15 +124,20 @@ impl<I: TransportIdentity> InMemoryTransport<I> {
                             | RouteId::CompleteQuery
                             | RouteId::KillQuery
                             | RouteId::Metrics => {
-                                handler
-                                    .as_ref()
-                                    .expect("Handler is set")
-                                    .handle(addr, BodyStream::from_bytes_stream(stream))
-                                    .await
+                                if let Some(handler) = handler.as_ref() {
+                                    handler
+                                        .handle(addr, BodyStream::from_bytes_stream(stream))
+                                        .await
+                                } else {
+                                    tracing::warn!("no handler is set to process {addr:?}");
+                                    Err(ApiError::NoHandler(addr.route))
+                                }
                             }
                         };
 
-                        ack.send(result).map_err(|_| "Channel closed").unwrap();
+                        if ack.send(result).is_err() {
+                            tracing::warn!("requester went away before receiving a response");
+                        }
                     }
                 }
             }
@@ -136,6 +147,8 @@ impl<I: TransportIdentity> InMemoryTransport<I> {
 
     fn get_channel(&self, dest: I) -> ConnectionTx<I> {
         self.connections
+            .lock()
+            .unwrap()
             .get(&dest)
             .unwrap_or_else(|| {
                 panic!(
@@ -146,6 +159,18 @@ impl<I: TransportIdentity> InMemoryTransport<I> {
             .clone()
     }
 
+    /// Replaces the link to `peer` with `tx`, so all subsequent requests to it are sent over
+    /// the new link. Unlike [`Setup::connect`], this works on a transport that is already
+    /// listening and does not require `peer` to be connected before. Requests that were already
+    /// sent over the old link are not affected.
+    ///
+    /// ## Panics
+    /// If the connections mutex is poisoned.
+    #[cfg(all(test, unit_test))]
+    pub(crate) fn replace_peer(&self, peer: I, tx: ConnectionTx<I>) {
+        self.connections.lock().unwrap().insert(peer, tx);
+    }
+
     /// Resets this transport, making it forget its state and be ready for processing another query.
     pub fn reset(&self) {
         self.record_streams.clear();
@@ -166,12 +191,28 @@ impl<I: TransportIdentity> Transport for Weak<InMemoryTransport<I>> {
         self.upgrade()
             .unwrap()
             .connections
+            .lock()
+            .unwrap()
             .keys()
             .copied()
             .collect::<Vec<_>>()
             .into_iter()
     }
 
+    fn peer_status(&self, peer: I) -> PeerStatus {
+        let Some(this) = self.upgrade() else {
+            return PeerStatus::Disconnected;
+        };
+        let connections = this.connections.lock().unwrap();
+        match connections.get(&peer) {
+            None => PeerStatus::Disconnected,
+            Some(tx) if tx.is_closed() => PeerStatus::Disconnected,
+            // the peer has not picked up any of the requests queued for it yet
+            Some(tx) if tx.capacity() == 0 => PeerStatus::Degraded,
+            Some(_) => PeerStatus::Connected,
+        }
+    }
+
     async fn send<
         D: Stream<Item = Vec<u8>> + Send + 'static,
         Q: QueryIdBinding,
@@ -374,8 +415,8 @@ mod tests {
                 },
                 routing::RouteId,
             },
-            HandlerBox, HelperIdentity, HelperResponse, InMemoryShardNetwork, OrderingSender, Role,
-            RoleAssignment, Transport, TransportIdentity,
+            ApiError, HandlerBox, HelperIdentity, HelperResponse, InMemoryShardNetwork,
+            OrderingSender, PeerStatus, Role, RoleAssignment, Transport, TransportIdentity,
         },
         protocol::{Gate, QueryId},
         sharding::ShardIndex,
@@ -442,6 +483,151 @@ mod tests {
         assert_eq!(expected, signal_rx.await.unwrap());
     }
 
+    #[tokio::test]
+    async fn no_handler() {
+        let (tx, transport) = Setup::new(HelperIdentity::ONE).into_active_conn(None);
+        let (ack_tx, ack_rx) = oneshot::channel();
+        tx.send((
+            Addr::from_route(
+                Some(HelperIdentity::TWO),
+                QueryConfig::new(TestMultiply, FieldType::Fp31, 1u32).unwrap(),
+            ),
+            InMemoryStream::wrap(stream::empty()),
+            ack_tx,
+        ))
+        .await
+        .unwrap();
+        assert!(matches!(
+            ack_rx.await.unwrap(),
+            Err(ApiError::NoHandler(RouteId::ReceiveQuery))
+        ));
+
+        // transport must keep processing requests
+        let expected = vec![vec![1], vec![2]];
+        send_and_ack(
+            &tx,
+            Addr::records(HelperIdentity::TWO, QueryId, Gate::from(STEP)),
+            stream::iter(expected.clone()),
+        )
+        .await;
+        let stream = Arc::downgrade(&transport)
+            .receive(HelperIdentity::TWO, (QueryId, Gate::from(STEP)))
+            .into_bytes_stream();
+        assert_eq!(expected, stream.collect::<Vec<_>>().await);
+    }
+
+    #[tokio::test]
+    async fn requester_gone() {
+        let (tx, transport) = Setup::new(HelperIdentity::ONE).into_active_conn(None);
+
+        // nobody is waiting for the response to this request
+        let (ack_tx, ack_rx) = oneshot::channel();
+        drop(ack_rx);
+        tx.send((
+            Addr::from_route(
+                Some(HelperIdentity::TWO),
+                QueryConfig::new(TestMultiply, FieldType::Fp31, 1u32).unwrap(),
+            ),
+            InMemoryStream::wrap(stream::empty()),
+            ack_tx,
+        ))
+        .await
+        .unwrap();
+
+        // malformed records request must be rejected
+        let (ack_tx, ack_rx) = oneshot::channel();
+        let mut addr = Addr::records(HelperIdentity::TWO, QueryId, Gate::from(STEP));
+        addr.gate = None;
+        tx.send((addr, InMemoryStream::wrap(stream::empty()), ack_tx))
+            .await
+            .unwrap();
+        assert!(matches!(
+            ack_rx.await.unwrap(),
+            Err(ApiError::BadRequest(_))
+        ));
+
+        // transport must keep processing requests
+        let expected = vec![vec![1], vec![2]];
+        send_and_ack(
+            &tx,
+            Addr::records(HelperIdentity::TWO, QueryId, Gate::from(STEP)),
+            stream::iter(expected.clone()),
+        )
+        .await;
+        let stream = Arc::downgrade(&transport)
+            .receive(HelperIdentity::TWO, (QueryId, Gate::from(STEP)))
+            .into_bytes_stream();
+        assert_eq!(expected, stream.collect::<Vec<_>>().await);
+    }
+
+    #[tokio::test]
+    async fn replace_peer() {
+        let mut setup1 = Setup::new(HelperIdentity::ONE);
+        let mut setup2 = Setup::new(HelperIdentity::TWO);
+        setup1.connect(&mut setup2);
+        let transport1 = setup1.start(None);
+        let _transport2 = setup2.start(None);
+
+        // transport is already listening, replace its link to H2 with a brand new one
+        let (new_tx, new_transport2) = Setup::new(HelperIdentity::TWO).into_active_conn(None);
+        transport1.replace_peer(HelperIdentity::TWO, new_tx);
+
+        let expected = vec![vec![1], vec![2]];
+        Arc::downgrade(&transport1)
+            .send(
+                HelperIdentity::TWO,
+                (RouteId::Records, QueryId, Gate::from(STEP)),
+                stream::iter(expected.clone()),
+            )
+            .await
+            .unwrap();
+
+        let stream = Arc::downgrade(&new_transport2)
+            .receive(HelperIdentity::ONE, (QueryId, Gate::from(STEP)))
+            .into_bytes_stream();
+        assert_eq!(expected, stream.collect::<Vec<_>>().await);
+    }
+
+    #[tokio::test]
+    async fn peer_status() {
+        let mut setup1 = Setup::new(HelperIdentity::ONE);
+        let mut setup2 = Setup::new(HelperIdentity::TWO);
+        setup1.connect(&mut setup2);
+        let transport1 = setup1.start(None);
+        let _transport2 = setup2.start(None);
+        let weak = Arc::downgrade(&transport1);
+
+        assert_eq!(PeerStatus::Connected, weak.peer_status(HelperIdentity::TWO));
+        assert_eq!(
+            PeerStatus::Disconnected,
+            weak.peer_status(HelperIdentity::THREE)
+        );
+
+        // peer that does not pick up requests
+        let (tx, _rx) = channel(1);
+        transport1.replace_peer(HelperIdentity::TWO, tx.clone());
+        let (ack, _) = oneshot::channel();
+        tx.try_send((
+            Addr::from_route(
+                Some(HelperIdentity::ONE),
+                (RouteId::Records, QueryId, Gate::from(STEP)),
+            ),
+            InMemoryStream::wrap(stream::empty()),
+            ack,
+        ))
+        .unwrap();
+        assert_eq!(PeerStatus::Degraded, weak.peer_status(HelperIdentity::TWO));
+
+        // peer that went away
+        let (tx, rx) = channel(1);
+        drop(rx);
+        transport1.replace_peer(HelperIdentity::TWO, tx);
+        assert_eq!(
+            PeerStatus::Disconnected,
+            weak.peer_status(HelperIdentity::TWO)
+        );
+    }
+
     #[tokio::test]
     async fn receive_not_ready() {
         let (tx, transport) = Setup::new(HelperIdentity::ONE).into_active_conn(None);
@@ -615,12 +801,12 @@ mod tests {
             .receive(HelperIdentity::ONE, (QueryId, gate))
             .into_bytes_stream();
 
-        tx.send(0, Fp31::try_from(0_u128).unwrap()).await;
+        tx.send(0, Fp31::try_from(0_u128).unwrap()).await.unwrap();
         // can't receive the value at index 0 because of buffering inside the sender
         assert_eq!(Some(Poll::Pending), poll_immediate(&mut recv).next().await);
 
         // make the sender ready
-        tx.send(1, Fp31::try_from(1_u128).unwrap()).await;
+        tx.send(1, Fp31::try_from(1_u128).unwrap()).await.unwrap();
         tx.close(2).await;
         // drop(tx);
 
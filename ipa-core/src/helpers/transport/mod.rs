@@ -321,6 +321,17 @@ impl<I: TransportIdentity, E: Debug> From<Vec<(I, E)>> for BroadcastError<I, E>
     }
 }
 
+/// Health of the connection to a peer, as seen by the local transport.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PeerStatus {
+    /// Requests to this peer are accepted without delay.
+    Connected,
+    /// The link to this peer is up, but it is not keeping up with requests sent to it.
+    Degraded,
+    /// The link to this peer is gone and requests to it will fail.
+    Disconnected,
+}
+
 /// Transport that supports per-query,per-step channels
 #[async_trait]
 pub trait Transport: Clone + Send + Sync + 'static {
@@ -341,6 +352,13 @@ pub trait Transport: Clone + Send + Sync + 'static {
         u32::try_from(self.peers().count()).expect("Number of peers is less than 4B")
     }
 
+    /// Reports the health of the connection to the `peer`. It helps to tell a slow peer from
+    /// the one that went away when a query stalls. Transports that cannot observe their
+    /// connections report every peer as [`PeerStatus::Connected`].
+    fn peer_status(&self, _peer: Self::Identity) -> PeerStatus {
+        PeerStatus::Connected
+    }
+
     /// Sends a new request to the given destination helper party.
     /// Depending on the specific request, it may or may not require acknowledgment by the remote
     /// party
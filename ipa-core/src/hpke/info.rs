@@ -1,4 +1,6 @@
-use crate::report::{Epoch, EventType, KeyIdentifier, NonAsciiStringError};
+use std::fmt::{Display, Formatter};
+
+use crate::report::{Epoch, EventType, KeyIdentifier, NonAsciiStringError, ParseEventTypeError};
 
 const DOMAIN: &str = "private-attribution";
 
@@ -10,8 +12,9 @@ const DOMAIN: &str = "private-attribution";
 /// It is not guaranteed that the same receiver can be used for anything else.
 ///
 /// [`info`]: https://www.rfc-editor.org/rfc/rfc9180.html#name-creating-the-encryption-con
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub struct Info<'a> {
+    pub(super) domain: &'a str,
     pub(super) key_id: KeyIdentifier,
     pub(super) epoch: Epoch,
     pub(super) event_type: EventType,
@@ -19,11 +22,54 @@ pub struct Info<'a> {
     pub(super) site_domain: &'a str,
 }
 
+/// String fields of [`Info`] that must be valid ASCII.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InfoField {
+    Domain,
+    HelperOrigin,
+    SiteDomain,
+}
+
+impl Display for InfoField {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Domain => "domain",
+            Self::HelperOrigin => "helper_origin",
+            Self::SiteDomain => "site_domain",
+        })
+    }
+}
+
+/// Owned counterpart of [`Info`], obtained by parsing its serialized form with
+/// [`Info::from_bytes`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct OwnedInfo {
+    pub key_id: KeyIdentifier,
+    pub epoch: Epoch,
+    pub event_type: EventType,
+    pub helper_origin: String,
+    pub site_domain: String,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum InfoParseError {
+    #[error("info does not start with the expected domain {0}")]
+    DomainMismatch(String),
+    #[error("info is missing a delimiter")]
+    MissingDelimiter,
+    #[error("info must end with {expected} bytes of key id, epoch and event type, got {actual}")]
+    BadTrailerLength { expected: usize, actual: usize },
+    #[error(transparent)]
+    NonAsciiString(#[from] NonAsciiStringError),
+    #[error(transparent)]
+    EventType(#[from] ParseEventTypeError),
+}
+
 impl<'a> Info<'a> {
-    /// Creates a new instance.
+    /// Creates a new instance for match key encryption.
     ///
     /// ## Errors
-    /// if helper or site origin is not a valid ASCII string.
+    /// if helper or site origin is not a valid ASCII string or contains NUL bytes.
     pub fn new(
         key_id: KeyIdentifier,
         epoch: Epoch,
@@ -31,17 +77,50 @@ impl<'a> Info<'a> {
         helper_origin: &'a str,
         site_domain: &'a str,
     ) -> Result<Self, NonAsciiStringError> {
+        Self::new_with_domain(
+            DOMAIN,
+            key_id,
+            epoch,
+            event_type,
+            helper_origin,
+            site_domain,
+        )
+    }
+
+    /// Creates a new instance bound to the given `domain`. Using a distinct domain for every
+    /// purpose HPKE is used for guarantees that ciphertexts produced for one of them cannot be
+    /// accepted by another.
+    ///
+    /// ## Errors
+    /// if domain, helper or site origin is not a valid ASCII string or contains NUL bytes. NUL
+    /// delimits these fields in [`Self::to_bytes`], so allowing it would let different origins
+    /// serialize to the same bytes.
+    pub fn new_with_domain(
+        domain: &'static str,
+        key_id: KeyIdentifier,
+        epoch: Epoch,
+        event_type: EventType,
+        helper_origin: &'a str,
+        site_domain: &'a str,
+    ) -> Result<Self, NonAsciiStringError> {
+        if !Self::is_valid_string(domain.as_bytes()) {
+            return Err(NonAsciiStringError::from(domain).with_field(InfoField::Domain));
+        }
+
         // If the types of errors returned from this function change, then the validation in
         // `EncryptedReport::from_bytes` may need to change as well.
-        if !helper_origin.is_ascii() {
-            return Err(helper_origin.into());
+        if !Self::is_valid_string(helper_origin.as_bytes()) {
+            return Err(
+                NonAsciiStringError::from(helper_origin).with_field(InfoField::HelperOrigin)
+            );
         }
 
-        if !site_domain.is_ascii() {
-            return Err(site_domain.into());
+        if !Self::is_valid_string(site_domain.as_bytes()) {
+            return Err(NonAsciiStringError::from(site_domain).with_field(InfoField::SiteDomain));
         }
 
         Ok(Self {
+            domain,
             key_id,
             epoch,
             event_type,
@@ -50,10 +129,15 @@ impl<'a> Info<'a> {
         })
     }
 
+    /// Checks that `bytes` can be used as a string field of [`Info`]: ASCII without NUL bytes.
+    pub(crate) fn is_valid_string(bytes: &[u8]) -> bool {
+        bytes.is_ascii() && !bytes.contains(&0)
+    }
+
     /// Converts this instance into an owned byte slice that can further be used to create HPKE
     /// sender or receiver context.
     pub(crate) fn to_bytes(&self) -> Box<[u8]> {
-        let info_len = DOMAIN.len()
+        let info_len = self.domain.len()
             + self.helper_origin.len()
             + self.site_domain.len()
             + 3 // account for 3 delimiters
@@ -62,7 +146,7 @@ impl<'a> Info<'a> {
             + std::mem::size_of_val(&self.event_type);
         let mut r = Vec::with_capacity(info_len);
 
-        r.extend_from_slice(DOMAIN.as_bytes());
+        r.extend_from_slice(self.domain.as_bytes());
         r.push(0);
         r.extend_from_slice(self.helper_origin.as_bytes());
         r.push(0);
@@ -78,4 +162,302 @@ impl<'a> Info<'a> {
 
         r.into_boxed_slice()
     }
+
+    /// Parses the output of [`Self::to_bytes`] back, checking that it was created for the
+    /// given `domain`.
+    ///
+    /// ## Errors
+    /// If `bytes` were not created for `domain` or are not a valid serialized [`Info`].
+    pub fn from_bytes(domain: &str, bytes: &[u8]) -> Result<OwnedInfo, InfoParseError> {
+        const TRAILER_LEN: usize = std::mem::size_of::<KeyIdentifier>()
+            + std::mem::size_of::<Epoch>()
+            + std::mem::size_of::<u8>();
+
+        let rest = bytes
+            .strip_prefix(domain.as_bytes())
+            .and_then(|rest| rest.strip_prefix(&[0]))
+            .ok_or_else(|| InfoParseError::DomainMismatch(domain.to_string()))?;
+
+        // Trailer may contain zero bytes, so it must not be split any further.
+        let mut parts = rest.splitn(3, |&b| b == 0);
+        let (Some(helper_origin), Some(site_domain), Some(trailer)) =
+            (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(InfoParseError::MissingDelimiter);
+        };
+
+        let &[key_id, epoch_hi, epoch_lo, event_type] = trailer else {
+            return Err(InfoParseError::BadTrailerLength {
+                expected: TRAILER_LEN,
+                actual: trailer.len(),
+            });
+        };
+
+        Ok(OwnedInfo {
+            key_id,
+            epoch: Epoch::from_be_bytes([epoch_hi, epoch_lo]),
+            event_type: EventType::try_from(event_type)?,
+            helper_origin: ascii_string(helper_origin, InfoField::HelperOrigin)?,
+            site_domain: ascii_string(site_domain, InfoField::SiteDomain)?,
+        })
+    }
+}
+
+/// Builds [`Info`] field by field, so origin strings can't be accidentally swapped.
+///
+/// Unless set, key id and epoch are zero, event type is [`EventType::Source`], origins are
+/// empty and the domain is the one used for match key encryption.
+#[derive(Clone)]
+pub struct InfoBuilder<'a> {
+    domain: &'static str,
+    key_id: KeyIdentifier,
+    epoch: Epoch,
+    event_type: EventType,
+    helper_origin: &'a str,
+    site_domain: &'a str,
+}
+
+impl Default for InfoBuilder<'_> {
+    fn default() -> Self {
+        Self {
+            domain: DOMAIN,
+            key_id: 0,
+            epoch: 0,
+            event_type: EventType::Source,
+            helper_origin: "",
+            site_domain: "",
+        }
+    }
+}
+
+impl<'a> InfoBuilder<'a> {
+    #[must_use]
+    pub fn domain(self, domain: &'static str) -> Self {
+        Self { domain, ..self }
+    }
+
+    #[must_use]
+    pub fn key_id(self, key_id: KeyIdentifier) -> Self {
+        Self { key_id, ..self }
+    }
+
+    #[must_use]
+    pub fn epoch(self, epoch: Epoch) -> Self {
+        Self { epoch, ..self }
+    }
+
+    #[must_use]
+    pub fn event_type(self, event_type: EventType) -> Self {
+        Self { event_type, ..self }
+    }
+
+    #[must_use]
+    pub fn helper_origin(self, helper_origin: &'a str) -> Self {
+        Self {
+            helper_origin,
+            ..self
+        }
+    }
+
+    #[must_use]
+    pub fn site_domain(self, site_domain: &'a str) -> Self {
+        Self {
+            site_domain,
+            ..self
+        }
+    }
+
+    /// Creates [`Info`] from the values set on this builder.
+    ///
+    /// ## Errors
+    /// if domain, helper or site origin is not a valid ASCII string or contains NUL bytes.
+    pub fn build(self) -> Result<Info<'a>, NonAsciiStringError> {
+        Info::new_with_domain(
+            self.domain,
+            self.key_id,
+            self.epoch,
+            self.event_type,
+            self.helper_origin,
+            self.site_domain,
+        )
+    }
+}
+
+fn ascii_string(bytes: &[u8], field: InfoField) -> Result<String, NonAsciiStringError> {
+    if Info::is_valid_string(bytes) {
+        Ok(bytes.iter().copied().map(char::from).collect())
+    } else {
+        Err(NonAsciiStringError::from(bytes).with_field(field))
+    }
+}
+
+#[cfg(all(test, unit_test))]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::{Info, InfoBuilder, InfoField, InfoParseError, OwnedInfo, DOMAIN};
+    use crate::report::EventType;
+
+    #[test]
+    fn domain_separation() {
+        let info = |domain| {
+            Info::new_with_domain(domain, 0, 1, EventType::Source, "foo", "bar")
+                .unwrap()
+                .to_bytes()
+        };
+
+        assert_eq!(
+            info(DOMAIN),
+            Info::new(0, 1, EventType::Source, "foo", "bar")
+                .unwrap()
+                .to_bytes()
+        );
+        assert_ne!(info(DOMAIN), info("private-attribution-other"));
+        assert_eq!(
+            info("private-attribution-other").len(),
+            info(DOMAIN).len() + "-other".len()
+        );
+    }
+
+    #[test]
+    fn non_ascii_fields() {
+        for (field, domain, helper_origin, site_domain) in [
+            (InfoField::Domain, "privé", "foo", "bar"),
+            (InfoField::HelperOrigin, DOMAIN, "fóo", "bar"),
+            (InfoField::SiteDomain, DOMAIN, "foo", "bär"),
+        ] {
+            let err =
+                Info::new_with_domain(domain, 0, 1, EventType::Source, helper_origin, site_domain)
+                    .err()
+                    .unwrap();
+
+            assert_eq!(Some(field), err.field());
+            assert!(
+                err.to_string()
+                    .starts_with(&format!("{field} contains non-ascii or NUL symbols: ")),
+                "{err}"
+            );
+        }
+    }
+
+    #[test]
+    fn nul_fields() {
+        for (field, domain, helper_origin, site_domain) in [
+            (InfoField::Domain, "a\0b", "foo", "bar"),
+            (InfoField::HelperOrigin, DOMAIN, "a\0b", "c"),
+            (InfoField::SiteDomain, DOMAIN, "a", "b\0c"),
+        ] {
+            let err =
+                Info::new_with_domain(domain, 0, 1, EventType::Source, helper_origin, site_domain)
+                    .err()
+                    .unwrap();
+
+            assert_eq!(Some(field), err.field());
+        }
+    }
+
+    #[test]
+    fn eq_matches_bytes() {
+        let infos = [
+            Info::new(0, 0, EventType::Source, "foo", "bar"),
+            Info::new(1, 0, EventType::Source, "foo", "bar"),
+            Info::new(0, 1, EventType::Source, "foo", "bar"),
+            Info::new(0, 0, EventType::Trigger, "foo", "bar"),
+            Info::new(0, 0, EventType::Source, "fo", "obar"),
+            Info::new(0, 0, EventType::Source, "foo", "bar"),
+            Info::new_with_domain("other", 0, 0, EventType::Source, "foo", "bar"),
+        ]
+        .map(Result::unwrap);
+
+        for a in &infos {
+            for b in &infos {
+                assert_eq!(a == b, a.to_bytes() == b.to_bytes());
+            }
+        }
+        assert_eq!(1, HashSet::from([&infos[0], &infos[5]]).len());
+    }
+
+    #[test]
+    fn builder() {
+        assert_eq!(
+            Info::new(0, 0, EventType::Source, "", "")
+                .unwrap()
+                .to_bytes(),
+            InfoBuilder::default().build().unwrap().to_bytes()
+        );
+        assert_eq!(
+            Info::new(3, 7, EventType::Trigger, "foo", "bar")
+                .unwrap()
+                .to_bytes(),
+            InfoBuilder::default()
+                .site_domain("bar")
+                .helper_origin("foo")
+                .event_type(EventType::Trigger)
+                .epoch(7)
+                .key_id(3)
+                .build()
+                .unwrap()
+                .to_bytes()
+        );
+        assert_eq!(
+            Some(InfoField::SiteDomain),
+            InfoBuilder::default()
+                .site_domain("bär")
+                .build()
+                .err()
+                .unwrap()
+                .field()
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let info = Info::new(
+            0,
+            0x1234,
+            EventType::Trigger,
+            "https://helper",
+            "site.example",
+        )
+        .unwrap()
+        .to_bytes();
+
+        assert_eq!(
+            OwnedInfo {
+                key_id: 0,
+                epoch: 0x1234,
+                event_type: EventType::Trigger,
+                helper_origin: "https://helper".to_string(),
+                site_domain: "site.example".to_string(),
+            },
+            Info::from_bytes(DOMAIN, &info).unwrap()
+        );
+    }
+
+    #[test]
+    fn parse_wrong_domain() {
+        let info = Info::new(0, 1, EventType::Source, "foo", "bar")
+            .unwrap()
+            .to_bytes();
+
+        assert!(matches!(
+            Info::from_bytes("private-attr", &info),
+            Err(InfoParseError::DomainMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn parse_truncated() {
+        let info = Info::new(0, 1, EventType::Source, "foo", "bar")
+            .unwrap()
+            .to_bytes();
+
+        assert!(matches!(
+            Info::from_bytes(DOMAIN, &info[..info.len() - 1]),
+            Err(InfoParseError::BadTrailerLength {
+                expected: 4,
+                actual: 3
+            })
+        ));
+    }
 }
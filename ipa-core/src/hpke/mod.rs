@@ -15,7 +15,7 @@ use typenum::U16;
 mod info;
 mod registry;
 
-pub use info::Info;
+pub use info::{Info, InfoBuilder, InfoField, InfoParseError, OwnedInfo};
 pub use registry::{
     KeyPair, KeyRegistry, PrivateKeyOnly, PrivateKeyRegistry, PublicKeyOnly, PublicKeyRegistry,
 };
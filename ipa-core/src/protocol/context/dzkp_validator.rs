@@ -612,7 +612,7 @@ impl Batch {
                 &proof_ctx,
                 prss_record_ids,
                 ProverTableIndices(self.get_field_values_prover()),
-            )
+            )?
         };
 
         let chunk_batch = BatchToVerify::generate_batch_to_verify(
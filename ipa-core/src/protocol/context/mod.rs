@@ -11,12 +11,12 @@ pub mod upgrade;
 mod batcher;
 pub mod validator;
 
-use std::{collections::HashMap, num::NonZeroUsize, pin::pin};
+use std::{collections::HashMap, future::Future, num::NonZeroUsize, pin::pin};
 
 use async_trait::async_trait;
 pub use dzkp_malicious::DZKPUpgraded as DZKPUpgradedMaliciousContext;
 pub use dzkp_semi_honest::DZKPUpgraded as DZKPUpgradedSemiHonestContext;
-use futures::{stream, Stream, StreamExt, TryStreamExt};
+use futures::{future::try_join, stream, Stream, StreamExt, TryStreamExt};
 use ipa_step::{Step, StepNarrow};
 pub use malicious::MaliciousProtocolSteps;
 use prss::{InstrumentedIndexedSharedRandomness, InstrumentedSequentialSharedRandomness};
@@ -106,6 +106,46 @@ pub trait Context: Clone + Send + Sync + SeqJoin {
     /// Requests data to be received from another MPC helper. Receive requests [`MpcReceivingEnd::receive`]
     /// can be issued from multiple threads.
     fn recv_channel<M: MpcMessage>(&self, role: Role) -> MpcReceivingEnd<M>;
+
+    /// Sends `msg` to `send_to` and receives a message of the same type from `recv_from`
+    /// for the same `record_id`. Send and receive are polled concurrently, so helpers can't
+    /// deadlock when they all do this exchange in a ring.
+    ///
+    /// ## Errors
+    /// If sending or receiving fails.
+    fn exchange<M: MpcMessage>(
+        &self,
+        send_to: Role,
+        recv_from: Role,
+        record_id: RecordId,
+        msg: M,
+    ) -> impl Future<Output = Result<M, Error>> + Send {
+        let send_channel = self.send_channel::<M>(send_to);
+        let recv_channel = self.recv_channel::<M>(recv_from);
+        async move {
+            let ((), received) = try_join(
+                send_channel.send(record_id, msg),
+                recv_channel.receive(record_id),
+            )
+            .await?;
+            Ok(received)
+        }
+    }
+
+    /// Receives the message for `record_id` from both peers concurrently. Returns the
+    /// message from the helper to the left first, and then the message from the helper to the
+    /// right.
+    ///
+    /// ## Errors
+    /// If receiving from either peer fails.
+    fn receive_from_peers<M: MpcMessage>(
+        &self,
+        record_id: RecordId,
+    ) -> impl Future<Output = Result<(M, M), Error>> + Send {
+        let left = self.recv_channel::<M>(self.role().peer(Direction::Left));
+        let right = self.recv_channel::<M>(self.role().peer(Direction::Right));
+        async move { Ok(try_join(left.receive(record_id), right.receive(record_id)).await?) }
+    }
 }
 
 pub trait UpgradableContext: Context {
@@ -620,7 +660,7 @@ pub trait DZKPContext: Context {
 mod tests {
     use std::{iter, iter::repeat, pin::Pin, task::Poll};
 
-    use futures::{future::join_all, ready, stream, stream::StreamExt, try_join, Stream};
+    use futures::{future::join_all, join, ready, stream, stream::StreamExt, try_join, Stream};
     use ipa_step::StepNarrow;
     use pin_project::pin_project;
     use rand::{
@@ -856,6 +896,57 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn exchange_in_ring() {
+        let world = TestWorld::default();
+        let received = world
+            .semi_honest((), |ctx, ()| async move {
+                let role = ctx.role();
+                ctx.set_total_records(1)
+                    .exchange(
+                        role.peer(Direction::Right),
+                        role.peer(Direction::Left),
+                        RecordId::FIRST,
+                        Fp31::truncate_from(role as u128),
+                    )
+                    .await
+                    .unwrap()
+            })
+            .await;
+
+        // everyone gets the value of the helper to their left
+        assert_eq!(
+            [Role::H3, Role::H1, Role::H2].map(|role| Fp31::truncate_from(role as u128)),
+            received
+        );
+    }
+
+    #[tokio::test]
+    async fn receive_from_peers() {
+        let world = TestWorld::default();
+        let [(h1_left, h1_right), _, _] = world
+            .semi_honest((), |ctx, ()| async move {
+                let ctx = ctx.set_total_records(1);
+                let role = ctx.role();
+                let value = Fp31::truncate_from(role as u128);
+                // both peers send to each helper at the same time
+                let left = ctx.send_channel::<Fp31>(role.peer(Direction::Left));
+                let right = ctx.send_channel::<Fp31>(role.peer(Direction::Right));
+                let (sent_left, sent_right, received) = join!(
+                    left.send(RecordId::FIRST, value),
+                    right.send(RecordId::FIRST, value),
+                    ctx.receive_from_peers::<Fp31>(RecordId::FIRST),
+                );
+                sent_left.unwrap();
+                sent_right.unwrap();
+                received.unwrap()
+            })
+            .await;
+
+        assert_eq!(Fp31::truncate_from(Role::H3 as u128), h1_left);
+        assert_eq!(Fp31::truncate_from(Role::H2 as u128), h1_right);
+    }
+
     /// validates that malicious upgrade can be called more than once on contexts narrowed down
     /// to unique steps
     #[tokio::test]
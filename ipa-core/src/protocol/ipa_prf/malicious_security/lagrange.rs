@@ -10,6 +10,7 @@ use crate::ff::{
 /// `https://en.wikipedia.org/wiki/Lagrange_polynomial`
 /// where the "x coordinates" of the input points are `x_0` to `x_N` are `F::ZERO` to `(N-1)*F::ONE`
 /// the degree of the polynomials is `N-1`
+#[derive(Clone)]
 pub struct CanonicalLagrangeDenominator<F: Field, const N: usize> {
     denominator: [F; N],
 }
@@ -51,6 +52,22 @@ where
     }
 }
 
+impl<F, const N: usize> CanonicalLagrangeDenominator<F, N>
+where
+    F: PrimeField,
+{
+    /// Evaluates the polynomial on each of the `points`, reusing the precomputed denominators.
+    /// Unlike [`LagrangeTable`], the output "x coordinates" don't need to be known in advance.
+    /// It is assumed that `y_coordinates` are the values of the polynomial on the canonical
+    /// _input_ "x coordinates" `F::ZERO` to `(N-1)*F::ONE`.
+    pub fn eval_at(&self, y_coordinates: &[F; N], points: &[F]) -> Vec<F> {
+        points
+            .iter()
+            .map(|x| LagrangeTable::<F, N, 1>::new(self, x).eval(y_coordinates)[0])
+            .collect()
+    }
+}
+
 impl<F, const N: usize> Default for CanonicalLagrangeDenominator<F, N>
 where
     F: PrimeField + TryFrom<u128>,
@@ -277,4 +294,35 @@ mod test {
             lagrange_canonical_using_from(input_points);
         }
     }
+
+    fn lagrange_eval_at(input_points: [TestField; 8], output_points: [TestField; 4]) {
+        let polynomial_monomial_form = MonomialFormPolynomial {
+            coefficients: input_points,
+        };
+        let output_expected: [TestField; 4] = polynomial_monomial_form.eval(&output_points);
+        let y_coordinates = polynomial_monomial_form.gen_y_values_of_canonical_points();
+        let denominator = CanonicalLagrangeDenominator::<TestField, 8>::new();
+
+        // evaluating on the canonical points gives back the inputs
+        let canonical_points = (0..8)
+            .map(|i| TestField::try_from(u128::try_from(i).unwrap()).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            y_coordinates.to_vec(),
+            denominator.eval_at(&y_coordinates, &canonical_points)
+        );
+
+        assert_eq!(
+            output_expected.to_vec(),
+            denominator.eval_at(&y_coordinates, &output_points)
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn proptest_lagrange_eval_at(input_points in prop::array::uniform8(any::<TestField>()), output_points in prop::array::uniform4(any::<TestField>()))
+        {
+            lagrange_eval_at(input_points, output_points);
+        }
+    }
 }
@@ -1,9 +1,12 @@
-use std::{array, borrow::Borrow, marker::PhantomData};
+use std::{array, borrow::Borrow, io, marker::PhantomData};
+
+use generic_array::GenericArray;
+use typenum::Unsigned;
 
 use crate::{
     error::Error::{self, DZKPMasks},
-    ff::{Fp61BitPrime, MultiplyAccumulate, MultiplyAccumulatorArray, PrimeField},
-    helpers::hashing::{compute_hash, hash_to_field},
+    ff::{Fp61BitPrime, MultiplyAccumulate, MultiplyAccumulatorArray, PrimeField, Serializable},
+    helpers::hashing::{compute_hash, hash_to_field, ZKP_CHALLENGE_DOMAIN},
     protocol::{
         context::{
             dzkp_field::{TABLE_U, TABLE_V},
@@ -20,6 +23,7 @@ use crate::{
         RecordId, RecordIdRange,
     },
     secret_sharing::SharedValue,
+    utils::arraychunks::ArrayChunkIterator,
 };
 
 /// This struct stores intermediate `uv` values.
@@ -79,6 +83,85 @@ where
         self.uv_chunks.iter()
     }
 
+    /// Serializes the stored `uv` values, so that proof generation can be resumed from them
+    /// later via [`Self::from_bytes`].
+    ///
+    /// The output starts with a header that contains `L` and the amount of stored tuples, both as
+    /// little-endian `u64`. It is followed by the `u` and then the `v` values of every chunk,
+    /// using the byte encoding of `F`.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let size = <F as Serializable>::Size::USIZE;
+        let mut bytes = Vec::with_capacity(Self::HEADER_LEN + self.uv_chunks.len() * 2 * L * size);
+        bytes.extend_from_slice(&(L as u64).to_le_bytes());
+        bytes.extend_from_slice(&(self.length as u64).to_le_bytes());
+
+        let mut buf = GenericArray::default();
+        for (u_values, v_values) in &self.uv_chunks {
+            for value in u_values.iter().chain(v_values) {
+                value.serialize(&mut buf);
+                bytes.extend_from_slice(&buf);
+            }
+        }
+
+        bytes
+    }
+
+    /// Restores `uv` values serialized by [`Self::to_bytes`].
+    ///
+    /// ## Errors
+    /// If the header does not match `L`, the amount of bytes does not match the amount of
+    /// tuples recorded in the header, or the bytes contain invalid field elements.
+    pub fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        fn invalid_data<E>(e: E) -> io::Error
+        where
+            E: Into<Box<dyn std::error::Error + Send + Sync>>,
+        {
+            io::Error::new(io::ErrorKind::InvalidData, e)
+        }
+
+        let size = <F as Serializable>::Size::USIZE;
+        let (header, body) = bytes
+            .split_at_checked(Self::HEADER_LEN)
+            .ok_or_else(|| invalid_data("uv values header is truncated"))?;
+        let (recursion_factor, length) = header.split_at(Self::HEADER_LEN / 2);
+        let recursion_factor = u64::from_le_bytes(recursion_factor.try_into().unwrap());
+        let length = u64::from_le_bytes(length.try_into().unwrap());
+        if recursion_factor != L as u64 {
+            return Err(invalid_data(format!(
+                "expected uv values with recursion factor {L}, got {recursion_factor}"
+            )));
+        }
+
+        let length = usize::try_from(length).map_err(invalid_data)?;
+        let chunk_len = 2 * L * size;
+        if body.len() != length.div_ceil(L) * chunk_len {
+            return Err(invalid_data(format!(
+                "expected {} bytes for {length} uv values, got {}",
+                length.div_ceil(L) * chunk_len,
+                body.len()
+            )));
+        }
+
+        let uv_chunks = body
+            .chunks_exact(chunk_len)
+            .map(|chunk| {
+                let mut values = chunk
+                    .chunks_exact(size)
+                    .map(|buf| F::deserialize(GenericArray::from_slice(buf)).map_err(invalid_data));
+                let mut u_values = [F::ZERO; L];
+                let mut v_values = [F::ZERO; L];
+                for value in u_values.iter_mut().chain(&mut v_values) {
+                    *value = values.next().unwrap()?;
+                }
+                Ok((u_values, v_values))
+            })
+            .collect::<io::Result<_>>()?;
+
+        Ok(Self { uv_chunks, length })
+    }
+
+    const HEADER_LEN: usize = 2 * size_of::<u64>();
+
     /// This function allows to generate and set masks
     ///
     /// It outputs `(p_mask_from_left_prover,q_mask_from_right_prover)`.
@@ -368,25 +451,94 @@ impl<F: PrimeField, const L: usize, const P: usize, const M: usize> ProofGenerat
             .take()
     }
 
-    fn gen_challenge_and_recurse<I: ProverLagrangeInput<F, L>, const N: usize>(
-        proof_left: &[F; P],
-        proof_right: &[F; P],
-        uv_iterator: I,
-    ) -> UVValues<F, N> {
-        let r: F = hash_to_field(
+    /// Computes the Fiat-Shamir challenge `r` from both shares of the proof.
+    pub fn challenge(proof_left: &[F; P], proof_right: &[F; P]) -> F {
+        hash_to_field(
+            ZKP_CHALLENGE_DOMAIN,
             &compute_hash(proof_left),
             &compute_hash(proof_right),
             L.try_into().unwrap(),
-        );
+        )
+    }
 
+    /// Computes the Fiat-Shamir challenge `r` from both shares of the proof and returns
+    /// the Lagrange table that evaluates polynomials at `r`.
+    pub fn challenge_lagrange_table(
+        proof_left: &[F; P],
+        proof_right: &[F; P],
+    ) -> LagrangeTable<F, L, 1> {
+        let r = Self::challenge(proof_left, proof_right);
         let denominator = CanonicalLagrangeDenominator::<F, L>::new();
-        let lagrange_table_r = LagrangeTable::<F, L, 1>::new(&denominator, &r);
+        LagrangeTable::<F, L, 1>::new(&denominator, &r)
+    }
+
+    fn gen_challenge_and_recurse<I: ProverLagrangeInput<F, L>, const N: usize>(
+        proof_left: &[F; P],
+        proof_right: &[F; P],
+        uv_iterator: I,
+    ) -> UVValues<F, N> {
+        let lagrange_table_r = Self::challenge_lagrange_table(proof_left, proof_right);
 
         uv_iterator
             .eval_at_r(&lagrange_table_r)
             .collect::<UVValues<F, N>>()
     }
 
+    /// Same as `gen_challenge_and_recurse`, but before computing the challenge it checks that
+    /// `proof_left` and `proof_right` add up to the locally computed `proof`.
+    ///
+    /// ## Errors
+    /// [`Error::InconsistentShares`] if the shares do not reconstruct `proof`.
+    pub fn gen_challenge_and_recurse_checked<I: ProverLagrangeInput<F, L>, const N: usize>(
+        proof: &[F; P],
+        proof_left: &[F; P],
+        proof_right: &[F; P],
+        uv_iterator: I,
+    ) -> Result<UVValues<F, N>, Error> {
+        Self::check_proof_shares(proof, proof_left, proof_right)?;
+
+        Ok(Self::gen_challenge_and_recurse(
+            proof_left,
+            proof_right,
+            uv_iterator,
+        ))
+    }
+
+    /// Checks that `proof_left` and `proof_right` add up to `proof` coefficient-wise. The
+    /// check is done in release builds too, since the challenge is derived from these shares.
+    ///
+    /// ## Errors
+    /// [`Error::InconsistentShares`] if the shares do not reconstruct `proof`.
+    pub fn check_proof_shares(
+        proof: &[F; P],
+        proof_left: &[F; P],
+        proof_right: &[F; P],
+    ) -> Result<(), Error> {
+        if (0..P).all(|i| proof_left[i] + proof_right[i] == proof[i]) {
+            Ok(())
+        } else {
+            Err(Error::InconsistentShares)
+        }
+    }
+
+    /// Lazy version of `gen_challenge_and_recurse`. It yields `uv` values for the next
+    /// recursion step as they are pulled, so they can be fed into [`Self::compute_proof_from_uv`]
+    /// without materializing all of them. Last chunk is padded with zeroes.
+    ///
+    /// `lagrange_table_r` must be obtained from [`Self::challenge_lagrange_table`].
+    pub fn gen_challenge_and_recurse_streaming<'a, I, const N: usize>(
+        lagrange_table_r: &'a LagrangeTable<F, L, 1>,
+        uv_iterator: I,
+    ) -> impl Iterator<Item = ([F; N], [F; N])> + 'a
+    where
+        I: ProverLagrangeInput<F, L> + 'a,
+    {
+        uv_iterator
+            .eval_at_r(lagrange_table_r)
+            .chunk_array::<N>()
+            .map(|chunk| (chunk.map(|(u, _)| u), chunk.map(|(_, v)| v)))
+    }
+
     fn gen_proof_shares_from_prss<C>(ctx: &C, record_ids: &mut RecordIdRange) -> ([F; P], [F; P])
     where
         C: Context,
@@ -413,6 +565,32 @@ impl<F: PrimeField, const L: usize, const P: usize, const M: usize> ProofGenerat
         proof_other_share
     }
 
+    /// Secret-shares the computed proof. The right share is drawn from PRSS, so the helper on
+    /// the right can generate it independently.
+    ///
+    /// It outputs `(share_of_proof_from_prover_left, my_proof_left_share, my_proof_right_share)`.
+    pub fn gen_proof_shares<C>(
+        ctx: &C,
+        record_ids: &mut RecordIdRange,
+        my_proof: [F; P],
+    ) -> ([F; P], [F; P], [F; P])
+    where
+        C: Context,
+    {
+        // generate proof shares from prss
+        let (share_of_proof_from_prover_left, my_proof_right_share) =
+            Self::gen_proof_shares_from_prss(ctx, record_ids);
+
+        // generate prover left proof
+        let my_proof_left_share = Self::gen_other_proof_share(my_proof, my_proof_right_share);
+
+        (
+            share_of_proof_from_prover_left,
+            my_proof_left_share,
+            my_proof_right_share,
+        )
+    }
+
     /// This function is a helper function that, given the computed proof, computes the shares of
     /// the proof, the challenge, and the next uv values.
     ///
@@ -420,37 +598,202 @@ impl<F: PrimeField, const L: usize, const P: usize, const M: usize> ProofGenerat
     /// where
     /// `share_of_proof_from_prover_left` from left has type `Vec<[F; P]>`,
     /// `my_proof_left_share` has type `Vec<[F; P]>`,
+    ///
+    /// ## Errors
+    /// [`Error::InconsistentShares`] if the proof shares do not add up to `my_proof`.
+    #[allow(clippy::type_complexity)]
     pub fn gen_artefacts_from_recursive_step<C, I, const N: usize>(
         ctx: &C,
         record_ids: &mut RecordIdRange,
         my_proof: [F; P],
         uv_iterator: I,
-    ) -> (UVValues<F, N>, [F; P], [F; P])
+    ) -> Result<(UVValues<F, N>, [F; P], [F; P]), Error>
     where
         C: Context,
         I: ProverLagrangeInput<F, L>,
     {
-        // generate proof shares from prss
-        let (share_of_proof_from_prover_left, my_proof_right_share) =
-            Self::gen_proof_shares_from_prss(ctx, record_ids);
-
-        // generate prover left proof
-        let my_proof_left_share = Self::gen_other_proof_share(my_proof, my_proof_right_share);
+        let (share_of_proof_from_prover_left, my_proof_left_share, my_proof_right_share) =
+            Self::gen_proof_shares(ctx, record_ids, my_proof);
 
         // compute next uv values
         // from iterator
-        let uv_values = Self::gen_challenge_and_recurse(
+        let uv_values = Self::gen_challenge_and_recurse_checked(
+            &my_proof,
             &my_proof_left_share,
             &my_proof_right_share,
             uv_iterator,
-        );
+        )?;
 
         //output uv values, prover left component and component from left
-        (
+        Ok((
             uv_values,
             share_of_proof_from_prover_left,
             my_proof_left_share,
-        )
+        ))
+    }
+}
+
+/// Recursion factors (λ) supported by [`run_proof`].
+#[cfg(all(test, unit_test))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProofRadix {
+    Two,
+    Four,
+    Eight,
+}
+
+/// Proves many independent statements with the same field and parameters.
+///
+/// The Lagrange denominator, and the table that extends `uv` values to the proof
+/// evaluation points, are computed once when the batch is created and shared by all
+/// statements, instead of being rebuilt for every proof.
+pub struct BatchProver<F: PrimeField, const L: usize, const P: usize, const M: usize> {
+    denominator: CanonicalLagrangeDenominator<F, L>,
+    lagrange_table: LagrangeTable<F, L, M>,
+}
+
+impl<F: PrimeField, const L: usize, const P: usize, const M: usize> Default
+    for BatchProver<F, L, P, M>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F: PrimeField, const L: usize, const P: usize, const M: usize> BatchProver<F, L, P, M> {
+    /// ## Panics
+    /// When the field size is too small for `L + M` evaluation points.
+    #[must_use]
+    pub fn new() -> Self {
+        let denominator = CanonicalLagrangeDenominator::new();
+        Self {
+            lagrange_table: LagrangeTable::from(denominator.clone()),
+            denominator,
+        }
+    }
+
+    /// Returns the table that extends `uv` values to the proof evaluation points.
+    pub fn lagrange_table(&self) -> &LagrangeTable<F, L, M> {
+        &self.lagrange_table
+    }
+
+    /// Same as [`ProofGenerator::compute_proof_from_uv`], using the table of this batch.
+    pub fn compute<J>(&self, uv: J) -> [F; P]
+    where
+        J: Iterator,
+        J::Item: Borrow<([F; L], [F; L])>,
+    {
+        ProofGenerator::<F, L, P, M>::compute_proof_from_uv(uv, &self.lagrange_table)
+    }
+
+    /// Same as [`ProofGenerator::challenge_lagrange_table`], but reuses the denominator of
+    /// this batch.
+    pub fn challenge_lagrange_table(
+        &self,
+        proof_left: &[F; P],
+        proof_right: &[F; P],
+    ) -> LagrangeTable<F, L, 1> {
+        let r = ProofGenerator::<F, L, P, M>::challenge(proof_left, proof_right);
+        LagrangeTable::new(&self.denominator, &r)
+    }
+
+    /// Same as [`ProofGenerator::gen_challenge_and_recurse_checked`], but reuses the denominator
+    /// of this batch.
+    ///
+    /// ## Errors
+    /// [`Error::InconsistentShares`] if the shares do not reconstruct `proof`.
+    pub fn gen_challenge_and_recurse_checked<I: ProverLagrangeInput<F, L>, const N: usize>(
+        &self,
+        proof: &[F; P],
+        proof_left: &[F; P],
+        proof_right: &[F; P],
+        uv_iterator: I,
+    ) -> Result<UVValues<F, N>, Error> {
+        ProofGenerator::<F, L, P, M>::check_proof_shares(proof, proof_left, proof_right)?;
+        let lagrange_table_r = self.challenge_lagrange_table(proof_left, proof_right);
+
+        Ok(uv_iterator.eval_at_r(&lagrange_table_r).collect())
+    }
+
+    /// Same as [`ProofGenerator::gen_artefacts_from_recursive_step`], but reuses the denominator
+    /// of this batch to evaluate the next uv values at the challenge point.
+    ///
+    /// ## Errors
+    /// [`Error::InconsistentShares`] if the proof shares do not add up to `my_proof`.
+    #[allow(clippy::type_complexity)]
+    pub fn gen_artefacts_from_recursive_step<C, I, const N: usize>(
+        &self,
+        ctx: &C,
+        record_ids: &mut RecordIdRange,
+        my_proof: [F; P],
+        uv_iterator: I,
+    ) -> Result<(UVValues<F, N>, [F; P], [F; P]), Error>
+    where
+        C: Context,
+        I: ProverLagrangeInput<F, L>,
+    {
+        let (share_of_proof_from_prover_left, my_proof_left_share, my_proof_right_share) =
+            ProofGenerator::<F, L, P, M>::gen_proof_shares(ctx, record_ids, my_proof);
+        let uv_values = self.gen_challenge_and_recurse_checked(
+            &my_proof,
+            &my_proof_left_share,
+            &my_proof_right_share,
+            uv_iterator,
+        )?;
+
+        Ok((
+            uv_values,
+            share_of_proof_from_prover_left,
+            my_proof_left_share,
+        ))
+    }
+}
+
+/// All proofs produced by [`run_proof`], one per recursion step, together with the challenges
+/// used to compress `uv` values between these steps.
+#[cfg(all(test, unit_test))]
+#[derive(Debug, Default)]
+pub struct RecursiveProof<F> {
+    pub proofs: Vec<Vec<F>>,
+    pub challenges: Vec<F>,
+}
+
+/// Generates the full sequence of proofs for `uv` with the recursion factor chosen at runtime,
+/// so callers don't need to pick the const parameters of [`ProofGenerator`] themselves.
+///
+/// The proof is not secret-shared here, so the Fiat-Shamir challenge is derived from the whole
+/// proof and a zero share. Recursion stops once `uv` fits into a single proof. Masks are not
+/// added to the last proof.
+#[cfg(all(test, unit_test))]
+pub fn run_proof<F: PrimeField>(uv: &[(F, F)], radix: ProofRadix) -> RecursiveProof<F> {
+    match radix {
+        ProofRadix::Two => run_proof_with::<F, 2, 3, 1>(uv),
+        ProofRadix::Four => run_proof_with::<F, 4, 7, 3>(uv),
+        ProofRadix::Eight => run_proof_with::<F, 8, 15, 7>(uv),
+    }
+}
+
+#[cfg(all(test, unit_test))]
+fn run_proof_with<F: PrimeField, const L: usize, const P: usize, const M: usize>(
+    uv: &[(F, F)],
+) -> RecursiveProof<F> {
+    let lagrange_table = LagrangeTable::<F, L, M>::from(CanonicalLagrangeDenominator::new());
+    let mut uv = uv.iter().copied().collect::<UVValues<F, L>>();
+    let mut output = RecursiveProof::default();
+    loop {
+        let proof = ProofGenerator::<F, L, P, M>::compute_proof_from_uv(uv.iter(), &lagrange_table);
+        output.proofs.push(proof.to_vec());
+        if uv.len() <= L {
+            break output;
+        }
+
+        let r = ProofGenerator::<F, L, P, M>::challenge(&proof, &[F::ZERO; P]);
+        let lagrange_table_r =
+            LagrangeTable::<F, L, 1>::new(&CanonicalLagrangeDenominator::new(), &r);
+        uv = ProverValues(uv.iter().copied())
+            .eval_at_r(&lagrange_table_r)
+            .collect();
+        output.challenges.push(r);
     }
 }
 
@@ -472,7 +815,10 @@ mod test {
                 Context,
             },
             ipa_prf::{
-                malicious_security::lagrange::{CanonicalLagrangeDenominator, LagrangeTable},
+                malicious_security::{
+                    lagrange::{CanonicalLagrangeDenominator, LagrangeTable},
+                    verifier::ProofVerifier,
+                },
                 FirstProofGenerator,
             },
             RecordId, RecordIdRange,
@@ -496,21 +842,86 @@ mod test {
             .collect::<UVValues<F, U>>()
     }
 
+    const U_1: [u128; 32] = [
+        0, 30, 0, 16, 0, 1, 0, 15, 0, 0, 0, 16, 0, 30, 0, 16, 29, 1, 1, 15, 0, 0, 1, 15, 2, 30, 30,
+        16, 0, 0, 30, 16,
+    ];
+    const V_1: [u128; 32] = [
+        0, 0, 0, 30, 0, 0, 0, 1, 30, 30, 30, 30, 0, 0, 30, 30, 0, 30, 0, 30, 0, 0, 0, 1, 0, 0, 1,
+        1, 0, 0, 1, 1,
+    ];
+    const PROOF_1: [u128; 7] = [0, 30, 29, 30, 5, 28, 13];
+    const U_2: [u128; 8] = [0, 0, 26, 0, 7, 18, 24, 13];
+    const V_2: [u128; 8] = [10, 21, 30, 28, 15, 21, 3, 3];
+
     #[test]
-    fn sample_proof() {
-        const U_1: [u128; 32] = [
-            0, 30, 0, 16, 0, 1, 0, 15, 0, 0, 0, 16, 0, 30, 0, 16, 29, 1, 1, 15, 0, 0, 1, 15, 2, 30,
-            30, 16, 0, 0, 30, 16,
-        ];
-        const V_1: [u128; 32] = [
-            0, 0, 0, 30, 0, 0, 0, 1, 30, 30, 30, 30, 0, 0, 30, 30, 0, 30, 0, 30, 0, 0, 0, 1, 0, 0,
-            1, 1, 0, 0, 1, 1,
-        ];
-        const PROOF_1: [u128; 7] = [0, 30, 29, 30, 5, 28, 13];
-        const PROOF_LEFT_1: [u128; 7] = [0, 11, 24, 8, 0, 4, 3];
-        const U_2: [u128; 8] = [0, 0, 26, 0, 7, 18, 24, 13];
-        const V_2: [u128; 8] = [10, 21, 30, 28, 15, 21, 3, 3];
+    fn checked_recursion_detects_tampered_share() {
+        let denominator = CanonicalLagrangeDenominator::<Fp31, 4>::new();
+        let lagrange_table = LagrangeTable::<Fp31, 4, 3>::from(denominator);
+        let uv_1 = zip_chunks(U_1, V_1);
+        let proof_1 = TestProofGenerator::compute_proof_from_uv(uv_1.iter(), &lagrange_table);
+
+        let proof_left_1: [Fp31; 7] = [0_u128, 11, 24, 8, 0, 4, 3].map(Fp31::truncate_from);
+        let proof_right_1: [Fp31; 7] = array::from_fn(|i| proof_1[i] - proof_left_1[i]);
+
+        // consistent shares give the same uv values as the unchecked recursion
+        assert_eq!(
+            TestProofGenerator::gen_challenge_and_recurse::<_, 4>(
+                &proof_left_1,
+                &proof_right_1,
+                ProverValues(uv_1.iter().copied()),
+            ),
+            TestProofGenerator::gen_challenge_and_recurse_checked(
+                &proof_1,
+                &proof_left_1,
+                &proof_right_1,
+                ProverValues(uv_1.iter().copied()),
+            )
+            .unwrap()
+        );
+
+        let mut tampered_proof_right_1 = proof_right_1;
+        tampered_proof_right_1[3] += Fp31::truncate_from(1_u128);
+        assert!(matches!(
+            TestProofGenerator::gen_challenge_and_recurse_checked::<_, 4>(
+                &proof_1,
+                &proof_left_1,
+                &tampered_proof_right_1,
+                ProverValues(uv_1.iter().copied()),
+            ),
+            Err(Error::InconsistentShares)
+        ));
 
+        // proof generation goes through the batch prover, which must catch it as well
+        let batch = BatchProver::<Fp31, 4, 7, 3>::new();
+        assert_eq!(
+            TestProofGenerator::gen_challenge_and_recurse::<_, 4>(
+                &proof_left_1,
+                &proof_right_1,
+                ProverValues(uv_1.iter().copied()),
+            ),
+            batch
+                .gen_challenge_and_recurse_checked(
+                    &proof_1,
+                    &proof_left_1,
+                    &proof_right_1,
+                    ProverValues(uv_1.iter().copied()),
+                )
+                .unwrap()
+        );
+        assert!(matches!(
+            batch.gen_challenge_and_recurse_checked::<_, 4>(
+                &proof_1,
+                &proof_left_1,
+                &tampered_proof_right_1,
+                ProverValues(uv_1.iter().copied()),
+            ),
+            Err(Error::InconsistentShares)
+        ));
+    }
+
+    #[test]
+    fn sample_proof() {
         const PROOF_2: [u128; 7] = [12, 6, 15, 8, 29, 30, 6];
         const PROOF_LEFT_2: [u128; 7] = [5, 26, 14, 9, 0, 25, 2];
         const U_3: [u128; 2] = [3, 3]; // will later be padded with zeroes
@@ -519,9 +930,10 @@ mod test {
         const PROOF_3: [u128; 7] = [12, 10, 0, 15, 16, 19, 5];
         const P_RANDOM_WEIGHT: u128 = 12;
         const Q_RANDOM_WEIGHT: u128 = 1;
+        const CHALLENGES: [u128; 2] = [22, 17];
 
         let denominator = CanonicalLagrangeDenominator::<Fp31, 4>::new();
-        let lagrange_table = LagrangeTable::<Fp31, 4, 3>::from(denominator);
+        let lagrange_table = LagrangeTable::<Fp31, 4, 3>::from(denominator.clone());
 
         // uv values in input format (iterator of tuples of arrays of length 4)
         let uv_1 = zip_chunks(U_1, V_1);
@@ -533,21 +945,12 @@ mod test {
             PROOF_1,
         );
 
-        // ZKP is secret-shared into two pieces
-        // proof_left comes from PRSS
-        let proof_left_1: [Fp31; 7] = PROOF_LEFT_1.map(Fp31::truncate_from);
-        let proof_right_1: [Fp31; 7] = zip(proof_1, proof_left_1)
-            .map(|(x, y)| x - y)
-            .collect::<Vec<_>>()
-            .try_into()
-            .unwrap();
-
-        // fiat-shamir
-        let uv_2 = TestProofGenerator::gen_challenge_and_recurse(
-            &proof_left_1,
-            &proof_right_1,
-            ProverValues(uv_1.iter().copied()),
-        );
+        // recurse at a pinned challenge, the same one the verifier tests use
+        let lagrange_table_r =
+            LagrangeTable::<Fp31, 4, 1>::new(&denominator, &Fp31::truncate_from(CHALLENGES[0]));
+        let uv_2 = ProverValues(uv_1.iter().copied())
+            .eval_at_r(&lagrange_table_r)
+            .collect::<UVValues<Fp31, 4>>();
         assert_eq!(uv_2, zip_chunks(U_2, V_2));
 
         // next iteration
@@ -557,6 +960,26 @@ mod test {
             PROOF_2,
         );
 
+        // streaming recursion produces the same values and the same proof
+        assert_eq!(
+            uv_2.iter().copied().collect::<Vec<_>>(),
+            TestProofGenerator::gen_challenge_and_recurse_streaming::<_, 4>(
+                &lagrange_table_r,
+                ProverValues(uv_1.iter().copied()),
+            )
+            .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            proof_2,
+            TestProofGenerator::compute_proof_from_uv(
+                TestProofGenerator::gen_challenge_and_recurse_streaming(
+                    &lagrange_table_r,
+                    ProverValues(uv_1.iter().copied()),
+                ),
+                &lagrange_table,
+            )
+        );
+
         // ZKP is secret-shared into two pieces
         // proof_left comes from PRSS
         let proof_left_2: [Fp31; 7] = PROOF_LEFT_2.map(Fp31::truncate_from);
@@ -566,14 +989,31 @@ mod test {
             .try_into()
             .unwrap();
 
-        // fiat-shamir
-        let uv_3 = TestProofGenerator::gen_challenge_and_recurse::<_, 4>(
-            &proof_left_2,
-            &proof_right_2,
-            ProverValues(uv_2.iter().copied()),
-        );
+        let uv_3 = ProverValues(uv_2.iter().copied())
+            .eval_at_r(&LagrangeTable::new(
+                &denominator,
+                &Fp31::truncate_from(CHALLENGES[1]),
+            ))
+            .collect::<UVValues<Fp31, 4>>();
         assert_eq!(uv_3, zip_chunks(U_3, V_3));
 
+        // fiat-shamir recursion pads the last chunk the same way as the streaming version
+        assert_eq!(
+            TestProofGenerator::gen_challenge_and_recurse::<_, 4>(
+                &proof_left_2,
+                &proof_right_2,
+                ProverValues(uv_2.iter().copied()),
+            )
+            .iter()
+            .copied()
+            .collect::<Vec<_>>(),
+            TestProofGenerator::gen_challenge_and_recurse_streaming::<_, 4>(
+                &TestProofGenerator::challenge_lagrange_table(&proof_left_2, &proof_right_2),
+                ProverValues(uv_2.iter().copied()),
+            )
+            .collect::<Vec<_>>()
+        );
+
         let masked_uv_3 = zip_chunks(
             [P_RANDOM_WEIGHT, U_3[1], 0, U_3[0]],
             [Q_RANDOM_WEIGHT, V_3[1], 0, V_3[0]],
@@ -588,6 +1028,99 @@ mod test {
         );
     }
 
+    #[test]
+    fn uv_values_serialization() {
+        let uv_2 = zip_chunks::<Fp31, 4, _, _>(U_2, V_2);
+        let bytes = uv_2.to_bytes();
+        assert_eq!(uv_2, UVValues::<Fp31, 4>::from_bytes(&bytes).unwrap());
+
+        // partial chunks are padded with zeroes and restored with the original length
+        let uv_3 = zip_chunks::<Fp31, 4, _, _>(U_2[..6].iter().copied(), V_2[..6].iter().copied());
+        assert_eq!(
+            uv_3,
+            UVValues::<Fp31, 4>::from_bytes(&uv_3.to_bytes()).unwrap()
+        );
+
+        assert!(UVValues::<Fp31, 8>::from_bytes(&bytes).is_err());
+        assert!(UVValues::<Fp31, 4>::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(UVValues::<Fp31, 4>::from_bytes(&bytes[..4]).is_err());
+
+        // 31 is not a valid Fp31 value
+        let mut invalid = bytes;
+        *invalid.last_mut().unwrap() = 31;
+        assert!(UVValues::<Fp31, 4>::from_bytes(&invalid).is_err());
+    }
+
+    #[test]
+    fn batch_prover() {
+        let batch = BatchProver::<Fp31, 4, 7, 3>::new();
+        let uv_1 = zip_chunks(U_1, V_1);
+        let uv_2 = zip_chunks(U_2, V_2);
+
+        let lagrange_table =
+            LagrangeTable::<Fp31, 4, 3>::from(CanonicalLagrangeDenominator::<Fp31, 4>::new());
+        let expected = [&uv_1, &uv_2]
+            .map(|uv| TestProofGenerator::compute_proof_from_uv(uv.iter(), &lagrange_table));
+        let proofs = [&uv_1, &uv_2].map(|uv| batch.compute(uv.iter()));
+        assert_eq!(expected, proofs);
+
+        let (u, _) = zip_chunks::<Fp31, 4, _, _>(U_2, V_2)
+            .iter()
+            .next()
+            .copied()
+            .unwrap();
+        assert_eq!(
+            TestProofGenerator::challenge_lagrange_table(&proofs[0], &proofs[1]).eval(&u),
+            batch
+                .challenge_lagrange_table(&proofs[0], &proofs[1])
+                .eval(&u),
+        );
+    }
+
+    #[test]
+    fn run_proof_radix() {
+        fn verify_all<const L: usize, const P: usize>(proof: &RecursiveProof<Fp31>, uv_sum: Fp31) {
+            let verifier = ProofVerifier::<Fp31, L, P>::new();
+            let mut expected_sum = uv_sum;
+            for (i, round_proof) in proof.proofs.iter().enumerate() {
+                let round_proof = <[Fp31; P]>::try_from(round_proof.as_slice()).unwrap();
+                // last proof is not followed by a challenge
+                let r = proof.challenges.get(i).copied().unwrap_or(Fp31::ZERO);
+                expected_sum = verifier.verify(&round_proof, r, expected_sum).unwrap();
+            }
+        }
+
+        let uv = zip(U_1, V_1)
+            .map(|(u, v)| (Fp31::truncate_from(u), Fp31::truncate_from(v)))
+            .collect::<Vec<_>>();
+        let uv_sum = uv.iter().fold(Fp31::ZERO, |acc, (u, v)| acc + *u * *v);
+
+        // 32 -> 8 -> 2
+        let proof = run_proof(&uv, ProofRadix::Four);
+        assert_eq!(3, proof.proofs.len());
+        assert_eq!(2, proof.challenges.len());
+        assert_eq!(
+            proof.proofs[0]
+                .iter()
+                .map(Fp31::as_u128)
+                .collect::<Vec<_>>(),
+            PROOF_1
+        );
+        verify_all::<4, 7>(&proof, uv_sum);
+
+        // 32 -> 16 -> 8 -> 4 -> 2
+        let proof = run_proof(&uv, ProofRadix::Two);
+        assert_eq!(5, proof.proofs.len());
+        assert!(proof.proofs.iter().all(|p| p.len() == 3));
+        verify_all::<2, 3>(&proof, uv_sum);
+
+        // 32 -> 4
+        let proof = run_proof(&uv, ProofRadix::Eight);
+        assert_eq!(2, proof.proofs.len());
+        assert!(proof.proofs.iter().all(|p| p.len() == 15));
+        verify_all::<8, 15>(&proof, uv_sum);
+    }
+
     #[test]
     fn check_uv_length() {
         run(|| async move {
@@ -610,12 +1143,14 @@ mod test {
             let world = TestWorld::default();
             let mut record_ids = RecordIdRange::ALL;
             let proof = TestProofGenerator::compute_proof_from_uv(uv_1.iter(), &lagrange_table);
-            let (uv_values, _, _) = TestProofGenerator::gen_artefacts_from_recursive_step::<_, _, 4>(
-                &world.contexts()[0],
-                &mut record_ids,
-                proof,
-                ProverValues(uv_1.iter().copied()),
-            );
+            let (uv_values, _, _) =
+                TestProofGenerator::gen_artefacts_from_recursive_step::<_, _, 4>(
+                    &world.contexts()[0],
+                    &mut record_ids,
+                    proof,
+                    ProverValues(uv_1.iter().copied()),
+                )
+                .unwrap();
 
             assert_eq!(7, uv_values.len());
         });
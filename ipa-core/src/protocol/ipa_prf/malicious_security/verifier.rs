@@ -104,6 +104,35 @@ pub fn compute_final_sum_share<F: PrimeField, const L: usize, const P: usize>(zk
     (1..L).fold(F::ZERO, |acc, i| acc + zkp[i])
 }
 
+/// Verifier for a single recursion step of the proofs generated by `ProofGenerator`, for
+/// the case when the whole proof is known, rather than shares of it. There is no such case in
+/// the protocol itself, but it allows checking the proofs end to end.
+///
+/// `L` is the recursion factor and `P` is the length of the proof, i.e. `2*L-1`.
+#[cfg(all(test, unit_test))]
+pub struct ProofVerifier<F: PrimeField, const L: usize, const P: usize> {
+    lagrange_denominator: CanonicalLagrangeDenominator<F, P>,
+}
+
+#[cfg(all(test, unit_test))]
+impl<F: PrimeField, const L: usize, const P: usize> ProofVerifier<F, L, P> {
+    pub fn new() -> Self {
+        Self {
+            lagrange_denominator: CanonicalLagrangeDenominator::new(),
+        }
+    }
+
+    /// Checks that `proof`, interpreted as points `G(0)..G(P-1)` on the proof polynomial,
+    /// satisfies `G(0) + ... + G(L-1) = expected_sum`.
+    ///
+    /// If it does, returns `G(r)` for the challenge `r`, which is the sum of `u*v` the proof
+    /// of the next recursion step must show. Returns `None` if the check fails.
+    pub fn verify(&self, proof: &[F; P], r: F, expected_sum: F) -> Option<F> {
+        (compute_sum_share::<F, L, P>(proof) == expected_sum)
+            .then(|| interpolate_at_r(proof, &r, &self.lagrange_denominator))
+    }
+}
+
 /// This function compresses the `u_or_v` values and returns the next `u_or_v` values.
 fn recurse_u_or_v<'a, F: PrimeField, const L: usize>(
     u_or_v: impl Iterator<Item = F> + 'a,
@@ -271,6 +300,8 @@ impl<F: PrimeField, const L: usize, I: Iterator<Item = [F; L]>> VerifierLagrange
 
 #[cfg(all(test, unit_test))]
 mod test {
+    use std::iter::zip;
+
     use rand::Rng;
 
     use super::*;
@@ -281,7 +312,10 @@ mod test {
                 dzkp_field::{tests::reference_convert, TABLE_U, TABLE_V},
                 dzkp_validator::{MultiplicationInputsBlock, BIT_ARRAY_LEN},
             },
-            ipa_prf::malicious_security::lagrange::{CanonicalLagrangeDenominator, LagrangeTable},
+            ipa_prf::malicious_security::{
+                lagrange::{CanonicalLagrangeDenominator, LagrangeTable},
+                prover::{ProofGenerator, ProverLagrangeInput, ProverValues, UVValues},
+            },
         },
         secret_sharing::SharedValue,
         test_executor::run_random,
@@ -351,6 +385,41 @@ mod test {
     const EXPECTED_P_FINAL: u128 = 27;
     const EXPECTED_Q_FINAL: u128 = 10;
 
+    #[test]
+    fn proof_verifier() {
+        run_random(|mut rng| async move {
+            let uv = (0..32)
+                .map(|_| (rng.gen::<Fp31>(), rng.gen::<Fp31>()))
+                .collect::<UVValues<Fp31, 4>>();
+            let expected_sum = uv
+                .iter()
+                .flat_map(|(u, v)| zip(u, v))
+                .fold(Fp31::ZERO, |acc, (u, v)| acc + *u * *v);
+            let proof = ProofGenerator::<Fp31, 4, 7, 3>::compute_proof_from_uv(
+                uv.iter(),
+                &LagrangeTable::from(CanonicalLagrangeDenominator::<Fp31, 4>::new()),
+            );
+
+            let verifier = ProofVerifier::<Fp31, 4, 7>::new();
+            let r = rng.gen::<Fp31>();
+            let next_sum = verifier.verify(&proof, r, expected_sum).unwrap();
+
+            // next recursion step must prove the sum of u*v evaluated at r
+            let table_r =
+                LagrangeTable::<Fp31, 4, 1>::new(&CanonicalLagrangeDenominator::new(), &r);
+            assert_eq!(
+                next_sum,
+                ProverValues(uv.iter().copied())
+                    .eval_at_r(&table_r)
+                    .fold(Fp31::ZERO, |acc, (u, v)| acc + u * v)
+            );
+
+            let mut tampered = proof;
+            tampered[1] += Fp31::truncate_from(1_u128);
+            assert_eq!(None, verifier.verify(&tampered, r, expected_sum));
+        });
+    }
+
     #[test]
     fn sample_proof_u() {
         let lagrange_denominator: CanonicalLagrangeDenominator<Fp31, 7> =
@@ -10,9 +10,11 @@ use futures::{
     stream::{self, unfold},
     FutureExt, Stream, StreamExt, TryStreamExt,
 };
+use ipa_step::CompactStep;
 
 use super::aggregation::breakdown_reveal::breakdown_reveal_aggregation;
 use crate::{
+    const_assert_eq,
     error::{Error, LengthError},
     ff::{
         boolean::Boolean,
@@ -58,6 +60,17 @@ use crate::{
 pub mod feature_label_dot_product;
 pub(crate) mod step;
 
+/// The maximum number of records a single user can have. Attribution panics when
+/// the histogram has more than this many rows.
+pub const MAX_ROWS: usize = 256;
+
+// The step count of `UserNthRowStep` has to be a literal, this keeps it in sync with `MAX_ROWS`.
+const_assert_eq!(
+    UserNthRowStep::STEP_COUNT as usize,
+    MAX_ROWS * (PerRowStep::STEP_COUNT as usize + 1),
+    "UserNthRowStep count does not match MAX_ROWS",
+);
+
 #[derive(Debug)]
 pub struct PrfShardedIpaInputRow<BK: SharedValue, TV: SharedValue, TS: SharedValue> {
     pub prf_of_match_key: u64,
@@ -391,6 +404,12 @@ fn set_up_contexts<C>(ctx: &C, histogram: &[usize]) -> Result<Vec<C>, Error>
 where
     C: Context,
 {
+    // `UserNthRowStep::from` is derived and only reports a generic out-of-bounds step index.
+    assert!(
+        histogram.len() <= MAX_ROWS,
+        "a user can have at most MAX_ROWS = {MAX_ROWS} records, got {}",
+        histogram.len()
+    );
     let mut context_per_row_depth = Vec::with_capacity(histogram.len());
     for (row_number, num_users_having_that_row_number) in histogram.iter().enumerate() {
         if row_number == 0 {
@@ -884,7 +903,7 @@ where
 pub mod tests {
     use std::{iter::repeat_n, num::NonZeroU32};
 
-    use super::{AttributionOutputs, PrfShardedIpaInputRow};
+    use super::{step::UserNthRowStep, AttributionOutputs, PrfShardedIpaInputRow, MAX_ROWS};
     use crate::{
         ff::{
             boolean::Boolean,
@@ -1164,15 +1183,15 @@ pub mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "Step index 64 out of bounds for UserNthRowStep with count 64.")]
+    #[should_panic(expected = "a user can have at most MAX_ROWS = 256 records, got 257")]
     fn attribution_too_many_records_per_user() {
         run(|| async move {
             let world = TestWorld::default();
 
             let records: Vec<PreShardedAndSortedOPRFTestInput<BA5, BA3, BA20>> =
-                repeat_n(oprf_test_input(123, false, 17, 0), 65).collect();
+                repeat_n(oprf_test_input(123, false, 17, 0), MAX_ROWS + 1).collect();
 
-            let histogram = repeat_n(1, 65).collect::<Vec<_>>();
+            let histogram = repeat_n(1, MAX_ROWS + 1).collect::<Vec<_>>();
             let histogram_ref = histogram.as_slice();
 
             world
@@ -1190,6 +1209,18 @@ pub mod tests {
                 .await;
         });
     }
+
+    #[test]
+    fn user_nth_row_step_bounds() {
+        let _ = UserNthRowStep::from(MAX_ROWS - 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Step index 256 out of bounds for UserNthRowStep with count 256.")]
+    fn user_nth_row_step_out_of_bounds() {
+        let _ = UserNthRowStep::from(MAX_ROWS);
+    }
+
     #[test]
     fn capping_bugfix() {
         const HISTOGRAM: [usize; 10] = [5, 5, 5, 5, 5, 5, 5, 2, 1, 1];
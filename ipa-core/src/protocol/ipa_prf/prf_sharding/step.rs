@@ -1,7 +1,8 @@
 use ipa_step_derive::CompactStep;
 
+/// Keep the count in sync with [`MAX_ROWS`](super::MAX_ROWS).
 #[derive(CompactStep)]
-#[step(count = 64, child = AttributionPerRowStep, name = "row")]
+#[step(count = 256, child = AttributionPerRowStep, name = "row")]
 pub struct UserNthRowStep(usize);
 
 #[derive(CompactStep)]
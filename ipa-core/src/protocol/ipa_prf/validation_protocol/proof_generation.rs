@@ -10,8 +10,7 @@ use crate::{
         context::{dzkp_validator::MAX_PROOF_RECURSION, Context},
         ipa_prf::{
             malicious_security::{
-                lagrange::{CanonicalLagrangeDenominator, LagrangeTable},
-                prover::{ProverLagrangeInput, ProverValues},
+                prover::{BatchProver, ProverLagrangeInput, ProverValues},
                 FIRST_RECURSION_FACTOR as FRF,
             },
             CompressedProofGenerator, FirstProofGenerator,
@@ -78,6 +77,9 @@ impl ProofBatch {
     /// The function outputs `my_proofs_left_shares`, `shares_of_proofs_from_prover_left`,
     /// `p_mask_from_right_prover`, `q_mask_from_left_prover`
     ///
+    /// ## Errors
+    /// [`Error::InconsistentShares`] if the shares of a proof do not add up to that proof.
+    ///
     /// ## Panics
     /// Panics when the function fails to set the masks without overwritting `u` and `v` values.
     /// This only happens when there is an issue in the recursion.
@@ -85,33 +87,31 @@ impl ProofBatch {
         ctx: &C,
         mut prss_record_ids: RecordIdRange,
         uv_inputs: impl ProverLagrangeInput<Fp61BitPrime, FRF> + Clone,
-    ) -> (Self, Self, Fp61BitPrime, Fp61BitPrime)
+    ) -> Result<(Self, Self, Fp61BitPrime, Fp61BitPrime), Error>
     where
         C: Context,
     {
+        const FPL: usize = FirstProofGenerator::PROOF_LENGTH;
         const FLL: usize = FirstProofGenerator::LAGRANGE_LENGTH;
         const CRF: usize = CompressedProofGenerator::RECURSION_FACTOR;
         const CLL: usize = CompressedProofGenerator::LAGRANGE_LENGTH;
         const CPL: usize = CompressedProofGenerator::PROOF_LENGTH;
 
         // precomputation for first proof
-        let first_denominator = CanonicalLagrangeDenominator::<Fp61BitPrime, FRF>::new();
-        let first_lagrange_table = LagrangeTable::<Fp61BitPrime, FRF, FLL>::from(first_denominator);
+        let first_prover = BatchProver::<Fp61BitPrime, FRF, FPL, FLL>::new();
 
+        // every input is compressed into one uv value by the first recursion
+        let mut uv_len = 0;
         let first_proof = FirstProofGenerator::compute_proof(
             uv_inputs
                 .clone()
-                .extrapolate_y_values(&first_lagrange_table),
+                .extrapolate_y_values(first_prover.lagrange_table())
+                .inspect(|_| uv_len += 1),
         );
 
         // generate first proof from input iterator
-        let (mut uv_values, first_proof_from_left, my_first_proof_left_share) =
-            FirstProofGenerator::gen_artefacts_from_recursive_step(
-                ctx,
-                &mut prss_record_ids,
-                first_proof,
-                uv_inputs,
-            );
+        let (first_proof_from_left, my_first_proof_left_share, my_first_proof_right_share) =
+            FirstProofGenerator::gen_proof_shares(ctx, &mut prss_record_ids, first_proof);
 
         // `MAX_PROOF_RECURSION - 2` because:
         //  * The first level of recursion has already happened.
@@ -119,10 +119,8 @@ impl ProofBatch {
         let max_uv_values: usize =
             (CRF - 1) * CRF.pow(u32::try_from(MAX_PROOF_RECURSION - 2).unwrap());
         assert!(
-            uv_values.len() <= max_uv_values,
-            "Proof batch is too large: have {} uv_values, max is {}",
-            uv_values.len(),
-            max_uv_values,
+            uv_len <= max_uv_values,
+            "Proof batch is too large: have {uv_len} uv_values, max is {max_uv_values}",
         );
 
         // storage for other proofs
@@ -143,8 +141,45 @@ impl ProofBatch {
         let (q_mask_from_left_prover, my_q_mask) =
             ctx.prss().generate_fields(prss_record_ids.expect_next());
 
-        let denominator = CanonicalLagrangeDenominator::<Fp61BitPrime, CRF>::new();
-        let lagrange_table = LagrangeTable::<Fp61BitPrime, CRF, CLL>::from(denominator);
+        // all compressed proofs share the same Lagrange denominator and table
+        let prover = BatchProver::<Fp61BitPrime, CRF, CPL, CLL>::new();
+
+        // The output of the first recursion is the largest set of uv values, so it is not
+        // materialized unless it must take the masks. Instead, it is computed twice from the
+        // inputs: once for the next proof and once to compress it further.
+        let mut uv_values = if uv_len < CRF {
+            first_prover.gen_challenge_and_recurse_checked(
+                &first_proof,
+                &my_first_proof_left_share,
+                &my_first_proof_right_share,
+                uv_inputs,
+            )?
+        } else {
+            FirstProofGenerator::check_proof_shares(
+                &first_proof,
+                &my_first_proof_left_share,
+                &my_first_proof_right_share,
+            )?;
+            let first_lagrange_table_r = first_prover
+                .challenge_lagrange_table(&my_first_proof_left_share, &my_first_proof_right_share);
+            let first_recursion = || {
+                FirstProofGenerator::gen_challenge_and_recurse_streaming::<_, CRF>(
+                    &first_lagrange_table_r,
+                    uv_inputs.clone(),
+                )
+            };
+            let (uv_values, share_of_proof_from_prover_left, my_proof_left_share) = prover
+                .gen_artefacts_from_recursive_step(
+                    ctx,
+                    &mut prss_record_ids,
+                    prover.compute(first_recursion()),
+                    ProverValues(first_recursion()),
+                )?;
+            shares_of_proofs_from_prover_left.push(share_of_proof_from_prover_left);
+            my_proofs_left_shares.push(my_proof_left_share);
+
+            uv_values
+        };
 
         // The last recursion can only include (λ - 1) u/v value pairs, because it needs to put the
         // masks in the constant term. If we compress to `uv_values.len() == CRF`, then we need to
@@ -160,15 +195,13 @@ impl ProofBatch {
                 did_set_masks = true;
                 uv_values.set_masks(my_p_mask, my_q_mask).unwrap();
             }
-            let my_proof =
-                CompressedProofGenerator::compute_proof_from_uv(uv_values.iter(), &lagrange_table);
-            let (uv_values_new, share_of_proof_from_prover_left, my_proof_left_share) =
-                CompressedProofGenerator::gen_artefacts_from_recursive_step(
+            let (uv_values_new, share_of_proof_from_prover_left, my_proof_left_share) = prover
+                .gen_artefacts_from_recursive_step(
                     ctx,
                     &mut prss_record_ids,
-                    my_proof,
+                    prover.compute(uv_values.iter()),
                     ProverValues(uv_values.iter().copied()),
-                );
+                )?;
             shares_of_proofs_from_prover_left.push(share_of_proof_from_prover_left);
             my_proofs_left_shares.push(my_proof_left_share);
 
@@ -183,12 +216,12 @@ impl ProofBatch {
             first_proof: first_proof_from_left,
             proofs: shares_of_proofs_from_prover_left,
         };
-        (
+        Ok((
             my_batch_left_shares,
             shares_of_batch_from_left_prover,
             p_mask_from_right_prover,
             q_mask_from_left_prover,
-        )
+        ))
     }
 
     /// This function sends a `Proof` to the party on the left
@@ -314,7 +347,8 @@ mod test {
                         &ctx.narrow("generate_batch"),
                         RecordIdRange::ALL,
                         ProverValues(uv_values_iter_ref.clone()),
-                    );
+                    )
+                    .unwrap();
 
                     BatchToVerify::generate_batch_to_verify(
                         ctx.narrow("generate_batch"),
@@ -12,7 +12,7 @@ use crate::{
     error::{Error, UnwrapInfallible},
     ff::{Fp61BitPrime, Serializable},
     helpers::{
-        hashing::{compute_hash, hash_to_field, Hash},
+        hashing::{compute_hash, hash_to_field, Hash, ZKP_CHALLENGE_DOMAIN},
         Direction, MpcMessage, TotalRecords,
     },
     protocol::{
@@ -146,7 +146,7 @@ impl BatchToVerify {
             .zip(my_hashes_prover_left.hashes.iter())
             .zip(once(exclude_large).chain(repeat(exclude_small)))
             .map(|((hash_left, hash_right), exclude)| {
-                hash_to_field(hash_left, hash_right, exclude)
+                hash_to_field(ZKP_CHALLENGE_DOMAIN, hash_left, hash_right, exclude)
             });
 
         // From the perspective of the *prover_right*, _left_ is this helper and _right_ is the other verifier
@@ -156,7 +156,7 @@ impl BatchToVerify {
             .zip(other_hashes_prover_right.hashes.iter())
             .zip(once(exclude_large).chain(repeat(exclude_small)))
             .map(|((hash_left, hash_right), exclude)| {
-                hash_to_field(hash_left, hash_right, exclude)
+                hash_to_field(ZKP_CHALLENGE_DOMAIN, hash_left, hash_right, exclude)
             });
 
         (
@@ -564,7 +564,8 @@ pub mod test {
                             &ctx.narrow("generate_batch"),
                             RecordIdRange::ALL,
                             ProverValues(uv_values_iter_ref.clone()),
-                        );
+                        )
+                        .unwrap();
 
                         let batch_to_verify = BatchToVerify::generate_batch_to_verify(
                             ctx.narrow("generate_batch"),
@@ -680,7 +681,8 @@ pub mod test {
                             &ctx.narrow("generate_batch"),
                             RecordIdRange::ALL,
                             ProverValues(vec_my_u_and_v.into_iter()),
-                        );
+                        )
+                        .unwrap();
 
                         let batch_to_verify = BatchToVerify::generate_batch_to_verify(
                             ctx.narrow("generate_batch"),
@@ -786,7 +788,8 @@ pub mod test {
                             &ctx.narrow("generate_batch"),
                             RecordIdRange::ALL,
                             ProverValues(vec_my_u_and_v.into_iter()),
-                        );
+                        )
+                        .unwrap();
 
                         let batch_to_verify = BatchToVerify::generate_batch_to_verify(
                             ctx.narrow("generate_batch"),
@@ -878,7 +881,8 @@ pub mod test {
                         &ctx.narrow("generate_batch"),
                         RecordIdRange::ALL,
                         ProverValues(vec_my_u_and_v.into_iter()),
-                    );
+                    )
+                    .unwrap();
 
                     let batch_to_verify = BatchToVerify::generate_batch_to_verify(
                         ctx.narrow("generate_batch"),
@@ -42,8 +42,8 @@ use crate::{
     ff::{boolean_array::BA64, Serializable},
     helpers::BodyStream,
     hpke::{
-        open_in_place, seal_in_place, CryptError, EncapsulationSize, Info, PrivateKeyRegistry,
-        PublicKeyRegistry, TagSize,
+        open_in_place, seal_in_place, CryptError, EncapsulationSize, Info, InfoField,
+        PrivateKeyRegistry, PublicKeyRegistry, TagSize,
     },
     secret_sharing::{replicated::semi_honest::AdditiveShare as Replicated, SharedValue},
 };
@@ -69,7 +69,7 @@ pub type Epoch = u16;
 /// the future.
 ///
 /// ['ipa-issue']: https://github.com/patcg-individual-drafts/ipa/issues/38
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 #[repr(u8)]
 pub enum EventType {
     Trigger,
@@ -140,11 +140,40 @@ impl From<&EventType> for u8 {
 #[derive(Debug)]
 pub struct NonAsciiStringError {
     input: String,
+    field: Option<InfoField>,
+}
+
+impl NonAsciiStringError {
+    /// Tags this error with the HPKE info field that contained non-ASCII or NUL symbols.
+    #[must_use]
+    pub fn with_field(self, field: InfoField) -> Self {
+        Self {
+            field: Some(field),
+            ..self
+        }
+    }
+
+    /// Returns the HPKE info field that contained non-ASCII or NUL symbols, if known.
+    #[must_use]
+    pub fn field(&self) -> Option<InfoField> {
+        self.field
+    }
 }
 
 impl Display for NonAsciiStringError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "string contains non-ascii symbols: {}", self.input)
+        match self.field {
+            Some(field) => write!(
+                f,
+                "{field} contains non-ascii or NUL symbols: {}",
+                self.input
+            ),
+            None => write!(
+                f,
+                "string contains non-ascii or NUL symbols: {}",
+                self.input
+            ),
+        }
     }
 }
 
@@ -161,6 +190,7 @@ impl From<&'_ [u8]> for NonAsciiStringError {
                     .collect::<Vec<_>>(),
             )
             .unwrap(),
+            field: None,
         }
     }
 }
@@ -368,8 +398,10 @@ where
         }
         EventType::try_from(bytes[Self::EVENT_TYPE_OFFSET])?;
         let site_domain = &bytes[Self::SITE_DOMAIN_OFFSET..];
-        if !site_domain.is_ascii() {
-            return Err(NonAsciiStringError::from(site_domain).into());
+        if !Info::is_valid_string(site_domain) {
+            return Err(NonAsciiStringError::from(site_domain)
+                .with_field(InfoField::SiteDomain)
+                .into());
         }
         Ok(Self {
             data: bytes,
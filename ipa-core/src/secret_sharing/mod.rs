@@ -179,10 +179,10 @@ where
 #[cfg(all(test, unit_test))]
 mod tests {
     use crate::{
-        ff::{Field, Fp31},
+        ff::{Field, Fp31, U128Conversions},
         secret_sharing::{
-            replicated::{malicious, semi_honest},
-            Linear, LinearRefOps,
+            replicated::{malicious, semi_honest, ReplicatedSecretSharing},
+            Linear, LinearRefOps, SecretSharing, SharedValue,
         },
     };
 
@@ -241,6 +241,15 @@ mod tests {
         trait_bounds::<semi_honest::AdditiveShare<Fp31>, _>();
     }
 
+    #[test]
+    fn is_zero_share() {
+        let one = Fp31::truncate_from(1_u128);
+
+        assert!(semi_honest::AdditiveShare::<Fp31>::ZERO.is_zero_share());
+        assert!(!semi_honest::AdditiveShare::new(one, Fp31::ZERO).is_zero_share());
+        assert!(!semi_honest::AdditiveShare::new(Fp31::ZERO, one).is_zero_share());
+    }
+
     #[test]
     fn malicious() {
         arithmetic::<malicious::AdditiveShare<Fp31>, _>();
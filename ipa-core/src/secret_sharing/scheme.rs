@@ -9,6 +9,15 @@ use crate::ff::{AddSub, AddSubAssign, Field, GaloisField};
 /// Secret sharing scheme i.e. Replicated secret sharing
 pub trait SecretSharing<V: SharedValue>: Clone + Debug + Sized + Send + Sync + 'static {
     const ZERO: Self;
+
+    /// Returns `true` if this helper's share is the zero share. This is a local check, it
+    /// does not tell whether the shared secret is zero.
+    fn is_zero_share(&self) -> bool
+    where
+        Self: PartialEq,
+    {
+        *self == Self::ZERO
+    }
 }
 
 /// Secret share of a secret that has additive and multiplicative properties.
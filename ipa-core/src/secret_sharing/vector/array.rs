@@ -6,7 +6,7 @@ use std::{
 };
 
 use generic_array::{ArrayLength, GenericArray};
-use typenum::{Unsigned, U16, U256, U32, U64};
+use typenum::{Unsigned, U16, U2, U256, U3, U32, U4, U64, U8};
 
 use crate::{
     const_assert_eq,
@@ -386,6 +386,10 @@ macro_rules! impl_serializable {
     };
 }
 
+impl_serializable!(2, U2);
+impl_serializable!(3, U3);
+impl_serializable!(4, U4);
+impl_serializable!(8, U8);
 impl_serializable!(16, U16);
 impl_serializable!(32, U32);
 impl_serializable!(64, U64);
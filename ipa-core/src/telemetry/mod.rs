@@ -7,6 +7,8 @@ pub use step_stats::CsvExporter as StepStatsCsvExporter;
 pub mod labels {
     pub use ::ipa_step::descriptive::labels::STEP;
     pub const ROLE: &str = "role";
+    pub const FLUSH: &str = "flush";
+    pub const FILL: &str = "fill";
 }
 
 pub mod metrics {
@@ -14,6 +16,13 @@ pub mod metrics {
     pub const REQUESTS_RECEIVED: &str = "requests.received";
     pub const RECORDS_SENT: &str = "records.sent";
     pub const BYTES_SENT: &str = "bytes.sent";
+    /// Number of batches handed from send buffers to the network layer. Labeled by whether
+    /// the batch was full or flushed early because the channel was closed.
+    pub const SEND_BUFFER_FLUSHES: &str = "send.buffer.flushes";
+    /// How full batches handed from send buffers to the network layer were, relative to the
+    /// read size. Metrics only support counters, so this is a histogram with one counter per
+    /// bucket: the fill label is the upper bound of a bucket, in 10% increments.
+    pub const SEND_BUFFER_FILL_RATIO: &str = "send.buffer.fill_ratio";
     pub const INDEXED_PRSS_GENERATED: &str = "i.prss.gen";
     pub const SEQUENTIAL_PRSS_GENERATED: &str = "s.prss.gen";
     pub use ::ipa_step::descriptive::labels::STEP_NARROWED;
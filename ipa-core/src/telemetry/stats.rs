@@ -209,6 +209,22 @@ impl MetricAssertion<'_> {
         self.clone()
     }
 
+    /// Validates metric value for an arbitrary dimension.
+    /// ## Panics
+    /// Panics if value is not equal to expected
+    pub fn per_dimension<I: TryInto<u64>>(
+        &self,
+        name: &'static str,
+        value: &str,
+        expected: I,
+    ) -> Self {
+        let actual = self.get_dimension(name).get(value).copied();
+        let expected = expected.try_into().ok();
+
+        assert_eq!(expected, actual);
+        self.clone()
+    }
+
     fn get_dimension(&self, name: &'static str) -> &HashMap<String, u64> {
         self.snapshot.dimensions.get(name).unwrap_or_else(|| {
             panic!(
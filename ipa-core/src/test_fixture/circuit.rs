@@ -1,8 +1,15 @@
-use std::array;
+use std::{
+    array,
+    time::{Duration, Instant},
+};
 
 use futures::{future::join3, stream, StreamExt};
 use ipa_step::StepNarrow;
-use rand::distributions::{Distribution, Standard};
+use rand::{
+    distributions::{Distribution, Standard},
+    rngs::StdRng,
+    SeedableRng,
+};
 
 use crate::{
     ff::{Field, U128Conversions},
@@ -13,7 +20,7 @@ use crate::{
         step::{ProtocolStep, TestExecutionStep as Step},
         Gate, RecordId,
     },
-    rand::thread_rng,
+    rand::{thread_rng, Rng},
     secret_sharing::{replicated::semi_honest::AdditiveShare as Replicated, FieldSimd, IntoShares},
     seq_join::seq_join,
     test_fixture::{ReconstructArr, TestWorld, TestWorldConfig},
@@ -25,6 +32,15 @@ pub struct Inputs<F: Field + FieldSimd<N>, const N: usize> {
     b: Vec<Replicated<F, N>>,
 }
 
+/// Timings collected while running the arithmetic circuit.
+#[derive(Debug, Default)]
+pub struct CircuitStats {
+    /// Time spent on each multiplication layer, averaged over all records and helpers.
+    pub per_layer: Vec<Duration>,
+    /// Wall clock time of the whole circuit execution.
+    pub total: Duration,
+}
+
 impl<F: Field + FieldSimd<N>, const N: usize> Inputs<F, N> {
     fn new(a: Replicated<F, N>, b: Vec<Replicated<F, N>>) -> Self {
         Self { a, b }
@@ -41,15 +57,54 @@ where
     F: Field + FieldSimd<N>,
     Standard: Distribution<F>,
 {
-    let mut rng = thread_rng();
+    arithmetic_setup_with_seed(width, depth, thread_rng().gen::<u64>())
+}
+
+/// Same as [`arithmetic_setup`], but shares are generated from the given `seed`, so
+/// the same inputs can be reproduced across runs.
+///
+/// # Panics
+/// On functional errors, since this is a benchmark.
+#[must_use]
+pub fn arithmetic_setup_with_seed<F, const N: usize>(
+    width: u32,
+    depth: u16,
+    seed: u64,
+) -> [Vec<Inputs<F, N>>; 3]
+where
+    F: Field + FieldSimd<N>,
+    Standard: Distribution<F>,
+{
+    setup_values(
+        width,
+        depth,
+        F::ONE,
+        F::ONE,
+        &mut StdRng::seed_from_u64(seed),
+    )
+}
+
+/// Every accumulator is shared as `a` and every layer multiplies it by a share of `b`.
+fn setup_values<F, R, const N: usize>(
+    width: u32,
+    depth: u16,
+    a: F,
+    b: F,
+    rng: &mut R,
+) -> [Vec<Inputs<F, N>>; 3]
+where
+    F: Field + FieldSimd<N>,
+    R: Rng,
+    Standard: Distribution<F>,
+{
     let mut data = array::from_fn(|_| Vec::with_capacity(width as usize / N));
     for _ in 0..(width / u32::try_from(N).unwrap()) {
-        let [a0, a1, a2] = [F::ONE; N].share_with(&mut rng);
+        let [a0, a1, a2] = [a; N].share_with(rng);
         let mut b0 = Vec::with_capacity(depth as usize);
         let mut b1 = Vec::with_capacity(depth as usize);
         let mut b2 = Vec::with_capacity(depth as usize);
         for _ in 0..(depth as usize) {
-            let [s0, s1, s2] = [F::ONE; N].share_with(&mut rng);
+            let [s0, s1, s2] = [b; N].share_with(rng);
             b0.push(s0);
             b1.push(s1);
             b2.push(s2);
@@ -75,6 +130,84 @@ pub async fn arithmetic<F, const N: usize>(
     for<'a> Replicated<F, N>: SecureMul<SemiHonestContext<'a>>,
     [F; N]: IntoShares<Replicated<F, N>>,
     Standard: Distribution<F>,
+{
+    arithmetic_with_stats(width, depth, active_work, input_data, false).await;
+}
+
+/// Same as [`arithmetic`], but also reports how long each multiplication layer took.
+///
+/// # Panics
+/// On functional errors, since this is a benchmark.
+pub async fn arithmetic_timed<F, const N: usize>(
+    width: u32,
+    depth: u16,
+    active_work: usize,
+    input_data: [Vec<Inputs<F, N>>; 3],
+) -> CircuitStats
+where
+    F: Field + FieldSimd<N> + U128Conversions,
+    for<'a> Replicated<F, N>: SecureMul<SemiHonestContext<'a>>,
+    [F; N]: IntoShares<Replicated<F, N>>,
+    Standard: Distribution<F>,
+{
+    arithmetic_with_stats(width, depth, active_work, input_data, true).await
+}
+
+async fn arithmetic_with_stats<F, const N: usize>(
+    width: u32,
+    depth: u16,
+    active_work: usize,
+    input_data: [Vec<Inputs<F, N>>; 3],
+    timed: bool,
+) -> CircuitStats
+where
+    F: Field + FieldSimd<N> + U128Conversions,
+    for<'a> Replicated<F, N>: SecureMul<SemiHonestContext<'a>>,
+{
+    let (outputs, stats) = evaluate(width, depth, active_work, input_data, timed).await;
+    let sum = outputs.into_iter().map(|v| v.as_u128()).sum::<u128>();
+
+    assert_eq!(sum, u128::from(width));
+
+    stats
+}
+
+/// Creates an arithmetic circuit with the given width and depth, where every accumulator
+/// starts at `a` and is multiplied by `b` at each layer. Unlike [`arithmetic`], this checks
+/// every output against `a * b^depth`, so it catches errors that happen to cancel out
+/// when all values are one.
+///
+/// # Panics
+/// On functional errors, since this is a benchmark.
+pub async fn arithmetic_values<F, const N: usize>(width: u32, depth: u16, a: F, b: F)
+where
+    F: Field + FieldSimd<N>,
+    for<'a> Replicated<F, N>: SecureMul<SemiHonestContext<'a>>,
+    Standard: Distribution<F>,
+{
+    let input_data = setup_values(width, depth, a, b, &mut thread_rng());
+    let expected = (0..depth).fold(a, |acc, _| acc * b);
+
+    let (outputs, _) = evaluate(width, depth, width as usize, input_data, false).await;
+
+    assert_eq!(outputs.len(), width as usize / N * N);
+    for output in outputs {
+        assert_eq!(output, expected);
+    }
+}
+
+/// Runs the circuit over `input_data` and returns the reconstructed outputs. Per-layer
+/// timings are only collected if `timed` is set.
+async fn evaluate<F, const N: usize>(
+    width: u32,
+    depth: u16,
+    active_work: usize,
+    input_data: [Vec<Inputs<F, N>>; 3],
+    timed: bool,
+) -> (Vec<F>, CircuitStats)
+where
+    F: Field + FieldSimd<N>,
+    for<'a> Replicated<F, N>: SecureMul<SemiHonestContext<'a>>,
 {
     let active = NonZeroU32PowerOfTwo::try_from(active_work.next_power_of_two()).unwrap();
     let config = TestWorldConfig {
@@ -99,24 +232,44 @@ pub async fn arithmetic<F, const N: usize>(
             config.gateway_config.active_work(),
             stream::iter((0..(width / u32::try_from(N).unwrap())).zip(col_data)).map(
                 move |(record, Inputs { a, b })| {
-                    circuit(ctx.clone(), RecordId::from(record), depth, a, b)
+                    circuit(ctx.clone(), RecordId::from(record), depth, a, b, timed)
                 },
             ),
         )
         .collect::<Vec<_>>()
     });
 
+    let start = Instant::now();
     let (res0, res1, res2) = join3(fut0, fut1, fut2).await;
+    let total = start.elapsed();
 
-    let mut sum = 0;
-    for line in res0.into_iter().zip(res1).zip(res2) {
-        let ((s0, s1), s2) = line;
-        for col_sum in [s0, s1, s2].reconstruct_arr() {
-            sum += col_sum.as_u128();
+    let mut per_layer = Vec::new();
+    if timed {
+        let layers = res0
+            .iter()
+            .chain(&res1)
+            .chain(&res2)
+            .map(|(_, layers)| layers);
+        let count = u32::try_from(3 * res0.len()).unwrap();
+        per_layer = vec![Duration::ZERO; usize::from(depth)];
+        for layer_times in layers {
+            for (acc, time) in per_layer.iter_mut().zip(layer_times) {
+                *acc += *time;
+            }
+        }
+        for acc in &mut per_layer {
+            *acc /= count;
         }
     }
 
-    assert_eq!(sum, u128::from(width));
+    let outputs = res0
+        .into_iter()
+        .zip(res1)
+        .zip(res2)
+        .flat_map(|(((s0, _), (s1, _)), (s2, _))| [s0, s1, s2].reconstruct_arr())
+        .collect();
+
+    (outputs, CircuitStats { per_layer, total })
 }
 
 async fn circuit<'a, F, const N: usize>(
@@ -125,16 +278,77 @@ async fn circuit<'a, F, const N: usize>(
     depth: u16,
     mut a: Replicated<F, N>,
     b: Vec<Replicated<F, N>>,
-) -> Replicated<F, N>
+    timed: bool,
+) -> (Replicated<F, N>, Vec<Duration>)
 where
     F: Field + FieldSimd<N>,
     Replicated<F, N>: SecureMul<SemiHonestContext<'a>>,
 {
     assert_eq!(b.len(), usize::from(depth));
+    let mut layers = Vec::with_capacity(if timed { b.len() } else { 0 });
     for (stripe_ix, stripe) in b.iter().enumerate() {
         let stripe_ctx = ctx.narrow(&Step::Iter(stripe_ix));
+        let start = timed.then(Instant::now);
         a = a.multiply(stripe, stripe_ctx, record_id).await.unwrap();
+        if let Some(start) = start {
+            layers.push(start.elapsed());
+        }
     }
 
-    a
+    (a, layers)
+}
+
+#[cfg(all(test, unit_test))]
+mod tests {
+    use std::time::Duration;
+
+    use super::{arithmetic, arithmetic_setup_with_seed, arithmetic_timed, arithmetic_values};
+    use crate::{
+        ff::{Fp31, U128Conversions},
+        test_executor::run,
+    };
+
+    #[test]
+    fn setup_with_seed_is_deterministic() {
+        let [h0, h1, h2] = arithmetic_setup_with_seed::<Fp31, 1>(8, 4, 42);
+        let [o0, o1, o2] = arithmetic_setup_with_seed::<Fp31, 1>(8, 4, 42);
+
+        for (this, other) in [(h0, o0), (h1, o1), (h2, o2)] {
+            assert_eq!(this.len(), other.len());
+            for (this, other) in this.into_iter().zip(other) {
+                assert_eq!(this.a, other.a);
+                assert_eq!(this.b, other.b);
+            }
+        }
+    }
+
+    #[test]
+    fn arithmetic_with_seed() {
+        run(|| async {
+            arithmetic::<Fp31, 1>(8, 4, 4, arithmetic_setup_with_seed(8, 4, 42)).await;
+        });
+    }
+
+    #[test]
+    fn values() {
+        run(|| async {
+            for (a, b) in [(1_u128, 1_u128), (2, 3), (30, 29), (29, 30), (0, 17)] {
+                arithmetic_values::<Fp31, 1>(8, 4, Fp31::truncate_from(a), Fp31::truncate_from(b))
+                    .await;
+            }
+        });
+    }
+
+    #[test]
+    fn timed() {
+        run(|| async {
+            let stats =
+                arithmetic_timed::<Fp31, 1>(8, 4, 4, arithmetic_setup_with_seed(8, 4, 42)).await;
+
+            assert_eq!(stats.per_layer.len(), 4);
+            // Layers of a single record never overlap, so the average time spent
+            // on them can't exceed the total.
+            assert!(stats.total >= stats.per_layer.iter().sum::<Duration>());
+        });
+    }
 }
@@ -32,7 +32,9 @@ pub use hybrid_event_gen::{
 };
 use rand::{distributions::Standard, prelude::Distribution, rngs::mock::StepRng};
 use rand_core::{CryptoRng, RngCore};
-pub use sharing::{get_bits, into_bits, Reconstruct, ReconstructArr};
+pub use sharing::{
+    get_bits, into_bits, reconstruct_checked, Reconstruct, ReconstructArr, ShareConsistencyError,
+};
 #[cfg(feature = "in-memory-infra")]
 pub use world::{
     Distribute, Random as RandomInputDistribution, RoundRobin as RoundRobinInputDistribution,
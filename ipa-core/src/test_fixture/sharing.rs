@@ -2,6 +2,7 @@ use std::{borrow::Borrow, iter::zip, ops::Deref};
 
 use crate::{
     ff::{PrimeField, U128Conversions},
+    helpers::Role,
     secret_sharing::{
         replicated::{
             malicious::{AdditiveShare as MaliciousReplicated, ExtendableField},
@@ -70,6 +71,32 @@ impl<V: SharedValue> Reconstruct<V> for [&Replicated<V>; 3] {
     }
 }
 
+/// Shares held by two adjacent helpers disagree on the value that both of them are supposed to
+/// hold: the right share of `left` must be equal to the left share of `right`.
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+#[error("shares held by {left:?} and {right:?} are inconsistent")]
+pub struct ShareConsistencyError {
+    pub left: Role,
+    pub right: Role,
+}
+
+/// Same as [`Reconstruct::reconstruct`], but reports inconsistent shares instead of panicking.
+///
+/// ## Errors
+/// If the shares held by any pair of adjacent helpers do not overlap.
+pub fn reconstruct_checked<V: SharedValue>(
+    shares: &[Replicated<V>; 3],
+) -> Result<V, ShareConsistencyError> {
+    for (i, &left) in Role::all().iter().enumerate() {
+        let right = Role::all()[(i + 1) % 3];
+        if shares[i].right() != shares[(i + 1) % 3].left() {
+            return Err(ShareConsistencyError { left, right });
+        }
+    }
+
+    Ok(shares[0].left() + shares[1].left() + shares[2].left())
+}
+
 impl<V: SharedValue> Reconstruct<V> for [Replicated<V>; 3] {
     fn reconstruct(&self) -> V {
         self.each_ref().reconstruct()
@@ -282,3 +309,42 @@ impl<F: ExtendableField> ValidateMalicious<F>
         [v0.clone(), v1.clone(), v2.clone()].validate(r);
     }
 }
+
+#[cfg(all(test, unit_test))]
+mod tests {
+    use super::{reconstruct_checked, ShareConsistencyError};
+    use crate::{
+        ff::{Fp31, U128Conversions},
+        helpers::Role,
+        secret_sharing::{replicated::ReplicatedSecretSharing, IntoShares},
+        test_fixture::Reconstruct,
+    };
+
+    #[test]
+    fn checked() {
+        let value = Fp31::truncate_from(17_u128);
+        let shares = value.share();
+        assert_eq!(Ok(value), reconstruct_checked(&shares));
+        assert_eq!(value, shares.reconstruct());
+
+        for (i, (left, right)) in [
+            (Role::H3, Role::H1),
+            (Role::H1, Role::H2),
+            (Role::H2, Role::H3),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let mut corrupted = shares.clone();
+            corrupted[i] = ReplicatedSecretSharing::new(
+                corrupted[i].left() + Fp31::truncate_from(1_u128),
+                corrupted[i].right(),
+            );
+
+            assert_eq!(
+                Err(ShareConsistencyError { left, right }),
+                reconstruct_checked(&corrupted)
+            );
+        }
+    }
+}
@@ -116,6 +116,10 @@ pub struct TestWorld<S: ShardingScheme = NotSharded> {
 #[derive(Clone)]
 pub struct TestWorldConfig {
     pub gateway_config: GatewayConfig,
+    /// Per-role overrides of [`Self::gateway_config`], indexed by [`Role`]. Helpers without an
+    /// override use the shared config. This makes it possible to reproduce deployments where
+    /// helpers are configured differently, for example when one of them has less memory.
+    pub per_role_gateway: [Option<GatewayConfig>; 3],
     /// Level for metrics span. If set to the tracing level or above (controlled by `RUST_LOG` and
     /// `logging` module) will result in metrics being recorded by this test world instance.
     /// recorded by this test world unless `RUST_LOG` for this crate is set to
@@ -414,6 +418,7 @@ impl Default for TestWorldConfig {
                 active: 16.try_into().unwrap(),
                 ..Default::default()
             },
+            per_role_gateway: [None; 3],
             // Disable metrics by default because `logging` only enables `Level::INFO` spans.
             // Can be overridden by setting `RUST_LOG` environment variable to match this level.
             metrics_level: Level::DEBUG,
@@ -451,6 +456,12 @@ impl TestWorldConfig {
         self
     }
 
+    /// Returns the gateway config used by the helper that plays `role`.
+    #[must_use]
+    pub fn gateway_config(&self, role: Role) -> GatewayConfig {
+        self.per_role_gateway[role].unwrap_or(self.gateway_config)
+    }
+
     #[must_use]
     pub fn role_assignment(&self) -> &RoleAssignment {
         const DEFAULT_ASSIGNMENT: RoleAssignment = RoleAssignment::new([
@@ -855,9 +866,10 @@ impl<S: ShardingScheme> ShardWorld<S> {
         );
 
         let mut gateways = zip3_ref(&network.transports(), &transports).map(|(mpc, shard)| {
+            let role = config.role_assignment().role(mpc.identity());
             Gateway::new(
                 QueryId,
-                config.gateway_config,
+                config.gateway_config(role),
                 config.role_assignment().clone(),
                 Transport::clone_ref(mpc),
                 Transport::clone_ref(shard),
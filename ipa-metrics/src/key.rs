@@ -33,6 +33,27 @@ use crate::label::{Label, OwnedLabel, MAX_LABELS};
 
 #[macro_export]
 macro_rules! metric_name {
+    // Match when three key-value pairs are provided
+    // TODO: enforce uniqueness at compile time
+    ($metric:expr, $l1:expr => $v1:expr, $l2:expr => $v2:expr, $l3:expr => $v3:expr) => {{
+        use $crate::UniqueElements;
+        let labels = [
+            $crate::Label {
+                name: $l1,
+                val: $v1,
+            },
+            $crate::Label {
+                name: $l2,
+                val: $v2,
+            },
+            $crate::Label {
+                name: $l3,
+                val: $v3,
+            },
+        ]
+        .enforce_unique();
+        $crate::MetricName::from_parts($metric, labels)
+    }};
     // Match when two key-value pairs are provided
     // TODO: enforce uniqueness at compile time
     ($metric:expr, $l1:expr => $v1:expr, $l2:expr => $v2:expr) => {{
@@ -171,6 +192,19 @@ impl UniqueElements for [Label<'_>; 2] {
     }
 }
 
+impl UniqueElements for [Label<'_>; 3] {
+    fn enforce_unique(self) -> Self {
+        assert!(
+            self[0].name != self[1].name
+                && self[0].name != self[2].name
+                && self[1].name != self[2].name,
+            "label names must be unique"
+        );
+
+        self
+    }
+}
+
 impl<'a, const LABELS: usize> PartialEq<Name<'a, LABELS>> for OwnedName {
     fn eq(&self, other: &Name<'a, LABELS>) -> bool {
         self.key == other.key
@@ -279,6 +313,12 @@ mod tests {
         );
     }
 
+    #[test]
+    #[should_panic(expected = "label names must be unique")]
+    fn three_labels_unique() {
+        let _ = metric_name!("foo", "label_1" => &1, "label_2" => &2, "label_1" => &3);
+    }
+
     #[test]
     fn eq_is_consistent() {
         let a_name = metric_name!("foo", "label_1" => &1);